@@ -55,18 +55,7 @@ impl Element<'_> {
             panic!("Not enough bytes to read f32")
         };
         self.data = data;
-        #[cfg(any(debug_assertions, feature = "gltf_validations"))]
-        {
-            let out = f32::from_le_bytes(*out);
-            if out.is_infinite() || out.is_nan() {
-                bevy::log::warn_once!("Accessor contains Infinite or NaN f32 value");
-            }
-            out
-        }
-        #[cfg(not(any(debug_assertions, feature = "gltf_validations")))]
-        {
-            f32::from_le_bytes(*out)
-        }
+        f32::from_le_bytes(*out)
     }
 }
 
@@ -44,11 +44,10 @@ impl<'a, T> DenseData<'a, T> {
         let raw_index = index.checked_mul(stride)?;
         let raw_end_index = raw_index.checked_add(self.element_size())?;
 
-        (self.count() > index && raw_index < self.view.len() && raw_end_index < self.view.len())
-            .then(|| {
-                // Extract the requested data
-                &self.view[raw_index..raw_end_index]
-            })
+        (self.count() > index && raw_end_index <= self.view.len()).then(|| {
+            // Extract the requested data
+            &self.view[raw_index..raw_end_index]
+        })
     }
 
     /// Get the [Dimensions] of the data viewed by this accessor
@@ -1,6 +1,6 @@
 use gltf::accessor::sparse::IndexType;
 
-use crate::wrap::ElementShape;
+use crate::wrap::{ElementShape, ElementType};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Meta {
@@ -25,19 +25,22 @@ impl Meta {
 
     pub fn from_sparse_index(acc: &gltf::Accessor<'_>) -> Self {
         let sparse = acc.sparse().unwrap();
+        let index_type = match sparse.indices().index_type() {
+            IndexType::U8 => ElementType::U8,
+            IndexType::U16 => ElementType::U16,
+            IndexType::U32 => ElementType::U32,
+        };
 
         Self {
             //extension: None,
-            shape: ElementShape::from(acc),
-            elem_size: acc.size(),
+            shape: ElementShape::Scalar(index_type),
+            elem_size: index_type.size(),
             count: sparse.count(),
-            stride: sparse.indices().view().stride().unwrap_or(
-                match sparse.indices().index_type() {
-                    IndexType::U8 => 1,
-                    IndexType::U16 => 2,
-                    IndexType::U32 => 4,
-                },
-            ),
+            stride: sparse
+                .indices()
+                .view()
+                .stride()
+                .unwrap_or(index_type.size()),
             normalized: false,
         }
     }
@@ -54,4 +57,15 @@ impl Meta {
             normalized: acc.normalized(),
         }
     }
+
+    /// Meta-data for an empty list of `u8` sparse indices
+    pub fn empty_index() -> Self {
+        Self {
+            shape: ElementShape::Scalar(ElementType::U8),
+            elem_size: 1,
+            stride: 1,
+            count: 0,
+            normalized: false,
+        }
+    }
 }
@@ -29,8 +29,23 @@ impl<'a, T> SparseData<'a, T> {
         }
     }
 
+    /// Create sparse data without any replaced elements, so every element is
+    /// read from `base` or is zero
+    pub(crate) fn without_replacements(meta: Meta, base: Option<DenseData<'a, T>>) -> Self {
+        Self {
+            meta,
+            base,
+            indices: IndexData::U8(DenseData::new(Meta::empty_index(), &[])),
+            values: DenseData::new(Meta { count: 0, ..meta }, &[]),
+        }
+    }
+
     /// Get the raw bytes of an element from the accessor
     pub fn get_raw(&self, index: usize) -> Option<&'a [u8]> {
+        if index >= self.meta.count {
+            return None;
+        }
+
         match self.indices.find_replacement(index) {
             Some(replace_idx) => self.values.get_raw(replace_idx),
             None => self
@@ -120,6 +135,10 @@ where
 {
     ///  Get an element from this accessor interpreted a s rust data
     pub fn get(&self, index: usize) -> Option<T::Item> {
+        if index >= self.meta.count {
+            return None;
+        }
+
         match self.indices.find_replacement(index) {
             Some(replace_idx) => self.values.get(replace_idx),
             None => self
@@ -160,19 +179,16 @@ where
             return None;
         }
 
-        match self.replace.peek() {
-            Some((idx, _)) if *idx == self.counter => {
-                self.counter += 1;
-                Some(self.replace.next().unwrap().1)
-            }
-            _ => {
-                if let Some(ref mut base) = self.base {
-                    base.next()
-                } else {
-                    Some(T::zero(self.meta.shape))
-                }
-            }
-        }
+        // The base is always advanced so it stays in step with replacements
+        let base = self.base.as_mut().and_then(|base| base.next());
+
+        let item = match self.replace.peek() {
+            Some((idx, _)) if *idx == self.counter => self.replace.next().map(|(_, v)| v),
+            _ => base.or_else(|| Some(T::zero(self.meta.shape))),
+        };
+
+        self.counter += 1;
+        item
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -216,11 +232,13 @@ impl<'a> IndexData<'a> {
     pub fn find_replacement(&self, index: usize) -> Option<usize> {
         macro_rules! bin_search {
             ($d:expr) => {{
+                // Search the half-open range `left..right`, sparse indices are
+                // strictly increasing
                 let mut left = 0;
-                let mut right = $d.count() - 1;
+                let mut right = $d.count();
 
-                while left != right {
-                    let idx = (left + right).div_ceil(2);
+                while left < right {
+                    let idx = left + (right - left) / 2;
 
                     let replaces = $d.get(idx)?;
 
@@ -231,9 +249,7 @@ impl<'a> IndexData<'a> {
                     }
                 }
 
-                // Check the final index
-                $d.get(left)
-                    .and_then(|r| (r as usize == index).then_some(left))
+                None
             }};
         }
 
@@ -279,3 +295,134 @@ impl Iterator for IndexIter<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrap::{ElementShape, ElementType};
+
+    const COUNT: usize = 6;
+    const BASE: [u32; COUNT] = [1, 2, 3, 4, 5, 6];
+    /// Replaces the first and last elements and one in the middle
+    const REPLACED: [usize; 3] = [0, 2, COUNT - 1];
+    const VALUES: [u32; 3] = [10, 30, 60];
+
+    fn meta(ty: ElementType, count: usize) -> Meta {
+        Meta {
+            shape: ElementShape::Scalar(ty),
+            elem_size: ty.size(),
+            stride: ty.size(),
+            count,
+            normalized: false,
+        }
+    }
+
+    fn u32_bytes(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// Encodes [REPLACED] with the given index type
+    fn index_bytes(ty: ElementType) -> Vec<u8> {
+        REPLACED
+            .iter()
+            .flat_map(|&i| match ty {
+                ElementType::U8 => vec![i as u8],
+                ElementType::U16 => (i as u16).to_le_bytes().to_vec(),
+                ElementType::U32 => (i as u32).to_le_bytes().to_vec(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    fn index_data(ty: ElementType, bytes: &[u8]) -> IndexData<'_> {
+        let meta = meta(ty, REPLACED.len());
+        match ty {
+            ElementType::U8 => IndexData::U8(DenseData::new(meta, bytes)),
+            ElementType::U16 => IndexData::U16(DenseData::new(meta, bytes)),
+            ElementType::U32 => IndexData::U32(DenseData::new(meta, bytes)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Checks that [SparseData::iter] and [SparseData::get] both produce
+    /// `expected`
+    fn assert_elements(data: &SparseData<'_, u32>, expected: &[u32]) {
+        let iter = data.iter();
+        assert_eq!(iter.len(), expected.len());
+        assert_eq!(iter.collect::<Vec<_>>(), expected);
+
+        let got: Vec<_> = (0..data.count()).map(|i| data.get(i)).collect();
+        let expected: Vec<_> = expected.iter().copied().map(Some).collect();
+        assert_eq!(got, expected);
+        assert_eq!(data.get(data.count()), None);
+        assert_eq!(data.get_raw(data.count()), None);
+    }
+
+    const INDEX_TYPES: [ElementType; 3] = [ElementType::U8, ElementType::U16, ElementType::U32];
+
+    #[test]
+    fn find_replacement() {
+        for ty in INDEX_TYPES {
+            let bytes = index_bytes(ty);
+            let indices = index_data(ty, &bytes);
+
+            assert_eq!(indices.count(), REPLACED.len());
+            let found: Vec<_> = (0..COUNT).map(|i| indices.find_replacement(i)).collect();
+            assert_eq!(
+                found,
+                [Some(0), None, Some(1), None, None, Some(2)],
+                "{ty:?} indices"
+            );
+            assert_eq!(indices.find_replacement(COUNT), None);
+        }
+    }
+
+    #[test]
+    fn sparse_over_base() {
+        let base = u32_bytes(&BASE);
+        let values = u32_bytes(&VALUES);
+
+        for ty in INDEX_TYPES {
+            let bytes = index_bytes(ty);
+            let data = SparseData::<u32>::new(
+                meta(ElementType::U32, COUNT),
+                Some(DenseData::new(meta(ElementType::U32, COUNT), &base)),
+                index_data(ty, &bytes),
+                DenseData::new(meta(ElementType::U32, VALUES.len()), &values),
+            );
+
+            assert_elements(&data, &[10, 2, 30, 4, 5, 60]);
+        }
+    }
+
+    #[test]
+    fn sparse_over_zeros() {
+        let values = u32_bytes(&VALUES);
+
+        for ty in INDEX_TYPES {
+            let bytes = index_bytes(ty);
+            let data = SparseData::<u32>::new(
+                meta(ElementType::U32, COUNT),
+                None,
+                index_data(ty, &bytes),
+                DenseData::new(meta(ElementType::U32, VALUES.len()), &values),
+            );
+
+            assert_elements(&data, &[10, 0, 30, 0, 0, 60]);
+        }
+    }
+
+    #[test]
+    fn without_replacements() {
+        let base = u32_bytes(&BASE);
+
+        let data = SparseData::<u32>::without_replacements(
+            meta(ElementType::U32, COUNT),
+            Some(DenseData::new(meta(ElementType::U32, COUNT), &base)),
+        );
+        assert_elements(&data, &BASE);
+
+        let data = SparseData::<u32>::without_replacements(meta(ElementType::U32, COUNT), None);
+        assert_elements(&data, &[0; COUNT]);
+    }
+}
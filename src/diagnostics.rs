@@ -0,0 +1,196 @@
+//! Non-fatal problems found while loading a glTF file
+//!
+//! Problems which do not prevent a glTF file from loading, such as skipped
+//! vertex attributes or non-finite accessor values, are recorded as
+//! [Diagnostic]s on the [Document](crate::wrap::Document). A
+//! [GltfTransformer](crate::GltfTransformer) can inspect them at any point
+//! during its load with [Document::diagnostics](crate::wrap::Document::diagnostics).
+use crate::error::ObjectPath;
+use bevy::utils::HashSet;
+use std::{fmt, sync::Mutex};
+
+/// How serious a [Diagnostic] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Information which does not indicate a problem with the asset
+    Info,
+    /// A problem with the asset which was worked around while loading
+    Warning,
+    /// A problem with the asset that likely produces incorrect results
+    Error,
+}
+
+/// Machine readable identifier for the kind of problem a [Diagnostic] reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// An accessor contained an infinite or NaN `f32` value
+    NonFiniteValue,
+    /// A primitive attribute could not be converted and was left out of the mesh
+    SkippedAttribute,
+    /// A child node was not spawned before its parent in a scene
+    MissingChildEntity,
+    /// A joint node of a skin was not spawned in the scene of a skinned mesh
+    MissingJointEntity,
+    /// A material property has no equivalent in the Bevy material and was ignored
+    UnsupportedMaterialProperty,
+    /// An extension in `extensionsUsed` is not supported and will be ignored
+    UnsupportedExtension,
+    /// The JSON data of an object's extensions could not be parsed
+    InvalidExtension,
+    /// A reflected component in node extras names an unregistered type or an
+    /// ambiguous short type path
+    UnknownReflectType,
+    /// Reflected components were requested without providing a type registry
+    MissingTypeRegistry,
+    /// A reflected component in node extras could not be inserted
+    InvalidReflectComponent,
+    /// Buffer data could not be loaded or read
+    DataUnavailable,
+    /// A buffer contains less data than its declared `byteLength`
+    BufferLength,
+    /// A buffer view exceeds the bounds of its buffer
+    ViewOutOfBounds,
+    /// A `byteStride` is invalid for the buffer view or accessor
+    InvalidStride,
+    /// Accessor data exceeds the bounds of its buffer view or is misaligned
+    AccessorOutOfBounds,
+    /// The declared `min` or `max` of an accessor does not match its data
+    AccessorBounds,
+    /// Sparse accessor indices are out of range or not strictly increasing
+    InvalidSparseIndices,
+    /// A vertex index or joint index references a missing element
+    IndexOutOfRange,
+    /// Primitive attributes have different element counts
+    AttributeCountMismatch,
+    /// `JOINTS_n` and `WEIGHTS_n` attributes are inconsistent
+    JointWeightMismatch,
+    /// A normal or tangent vector is not unit length
+    NonUnitVector,
+    /// Animation keyframe times are negative or not strictly increasing
+    AnimationInput,
+    /// A node is its own ancestor
+    NodeCycle,
+    /// A node is the child of more than one node
+    NodeMultipleParents,
+}
+
+/// A single non-fatal problem found while loading a glTF file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// The glTF object the problem was found in
+    pub path: ObjectPath,
+    /// How serious the problem is
+    pub severity: Severity,
+    /// The kind of problem
+    pub code: DiagnosticCode,
+    /// Human readable description of the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Create a new [Diagnostic]
+    pub fn new(
+        path: impl Into<ObjectPath>,
+        severity: Severity,
+        code: DiagnosticCode,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            severity,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Create a new [Severity::Warning] [Diagnostic]
+    pub fn warning(
+        path: impl Into<ObjectPath>,
+        code: DiagnosticCode,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(path, Severity::Warning, code, message)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} [{:?}] {}: {}",
+            self.severity, self.code, self.path, self.message
+        )
+    }
+}
+
+/// A collection of [Diagnostic]s reported while loading a glTF file
+///
+/// Reporting only requires a shared reference so that diagnostics can be
+/// recorded from any of the [wrap](crate::wrap) types.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    inner: Mutex<DiagnosticsInner>,
+}
+
+#[derive(Debug, Default)]
+struct DiagnosticsInner {
+    /// Recorded diagnostics in the order they were reported
+    items: Vec<Diagnostic>,
+    /// Set of the recorded diagnostics for fast deduplication
+    seen: HashSet<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Record a [Diagnostic]
+    ///
+    /// The diagnostic is also forwarded to the [bevy] log. Reporting the same
+    /// diagnostic more than once only records it the first time.
+    pub fn report(&self, diagnostic: Diagnostic) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.seen.contains(&diagnostic) {
+            return;
+        }
+
+        match diagnostic.severity {
+            Severity::Info => bevy::log::info!("{diagnostic}"),
+            Severity::Warning => bevy::log::warn!("{diagnostic}"),
+            Severity::Error => bevy::log::error!("{diagnostic}"),
+        }
+
+        inner.seen.insert(diagnostic.clone());
+        inner.items.push(diagnostic);
+    }
+
+    /// Returns a copy of all of the recorded [Diagnostic]s
+    pub fn to_vec(&self) -> Vec<Diagnostic> {
+        self.inner.lock().unwrap().items.clone()
+    }
+
+    /// Removes and returns all of the recorded [Diagnostic]s
+    pub fn take(&self) -> Vec<Diagnostic> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.seen.clear();
+        std::mem::take(&mut inner.items)
+    }
+
+    /// The number of recorded [Diagnostic]s
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().items.len()
+    }
+
+    /// Returns true if no [Diagnostic]s have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the highest [Severity] of all the recorded [Diagnostic]s
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.inner
+            .lock()
+            .unwrap()
+            .items
+            .iter()
+            .map(|d| d.severity)
+            .max()
+    }
+}
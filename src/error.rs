@@ -7,6 +7,7 @@ use gltf::{
     accessor::{DataType, Dimensions},
     mesh::Mode,
 };
+use std::fmt;
 use thiserror::Error;
 
 /// Result type for [GltfTransformer](crate::GltfTransformer) actions
@@ -24,6 +25,25 @@ pub enum Error {
     /// Invalid glTF file.
     #[error("invalid glTF file: {0}")]
     Gltf(#[from] gltf::Error),
+    /// The GLB header declared a file length smaller than the header itself.
+    #[error("invalid GLB header: declared length {0} is smaller than the header")]
+    GlbHeaderLength(u32),
+    /// The GLB binary chunk was not contained within the loaded file.
+    #[error("GLB binary chunk exceeded the bounds of the loaded file")]
+    GlbChunkOffset,
+    /// The glTF file requires an extension which is not supported by the
+    /// [GltfTransformer](crate::GltfTransformer).
+    #[error("glTF file requires unsupported extension `{0}`")]
+    UnsupportedExtension(String),
+    /// The JSON data of an extension could not be deserialized into its
+    /// [GltfExtension](crate::extension::GltfExtension) type.
+    #[error("failed to deserialize extension `{name}`: {source}")]
+    Extension {
+        /// The name of the extension
+        name: &'static str,
+        /// The underlying deserialization error
+        source: serde_json::Error,
+    },
     /// Binary blob is missing.
     #[error("binary blob is missing")]
     MissingBlob,
@@ -33,6 +53,12 @@ pub enum Error {
     /// Decoding the base64 mesh data failed.
     #[error("failed to decode base64 mesh data")]
     Base64Decode(#[from] base64::DecodeError),
+    /// A buffer view starts beyond the end of its loaded buffer data.
+    #[error("buffer view exceeds the bounds of its buffer")]
+    ViewOutOfBounds,
+    /// Accessor data starts beyond the end of its loaded buffer view data.
+    #[error("accessor exceeds the bounds of its buffer view")]
+    AccessorOutOfBounds,
     /// Unsupported buffer format.
     #[error("unsupported buffer format")]
     BufferFormatUnsupported,
@@ -42,6 +68,14 @@ pub enum Error {
     /// Invalid image mime type.
     #[error("invalid image mime type: {0}")]
     InvalidImageMimeType(String),
+    /// The format of a data URI image was recognized but the feature
+    /// required to load it is disabled.
+    #[error("{0}")]
+    ImageFeature(&'static str),
+    /// The format of a data URI image without a mime type could not be
+    /// identified.
+    #[error("could not identify image type")]
+    UnknownImageFormat,
     /// Error when loading a texture. Might be due to a disabled image file format feature.
     #[error("You may need to add the feature for the file format: {0}")]
     ImageError(#[from] TextureError),
@@ -83,4 +117,228 @@ pub enum Error {
     /// Could not construct the requested animation curve type
     #[error("invalid animation curve parameters")]
     InvalidAnimationCurve,
+    /// The application specific extras of a glTF object could not be
+    /// deserialized into the requested type.
+    #[error("failed to deserialize extras: {0}")]
+    Extras(serde_json::Error),
+    /// [Document::validate](crate::wrap::Document::validate) found errors
+    #[error("glTF validation failed with {} errors", .0.errors().count())]
+    Validation(crate::wrap::validate::ValidationReport),
+    /// An error which occurred while loading a specific glTF object
+    #[error("{path}: {source}")]
+    Context {
+        /// The path to the glTF object which failed to load
+        path: ObjectPath,
+        /// The underlying error
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Records that this error occurred within the glTF object described by
+    /// `segment`.
+    ///
+    /// Errors are propagated from the innermost object outwards, so each call
+    /// adds `segment` to the front of the recorded [ObjectPath].
+    pub fn context(self, segment: PathSegment) -> Self {
+        match self {
+            Self::Context { mut path, source } => {
+                path.0.insert(0, segment);
+                Self::Context { path, source }
+            }
+            source => Self::Context {
+                path: ObjectPath(vec![segment]),
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// Records that this error occurred within the glTF object at `path`.
+    ///
+    /// This is equivalent to calling [Error::context] for each segment of
+    /// `path` from innermost to outermost.
+    pub fn with_path(self, path: ObjectPath) -> Self {
+        path.0
+            .into_iter()
+            .rev()
+            .fold(self, |error, segment| error.context(segment))
+    }
+
+    /// Returns the path to the glTF object that caused this error if known
+    pub fn path(&self) -> Option<&ObjectPath> {
+        match self {
+            Self::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error without any [ObjectPath] context
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Self::Context { source, .. } => source.root_cause(),
+            x => x,
+        }
+    }
+}
+
+/// A path through the glTF object hierarchy, e.g. `Mesh3 "Door"/Primitive1/TEXCOORD_0/Accessor17`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ObjectPath(Vec<PathSegment>);
+
+impl ObjectPath {
+    /// Extends this path with a `segment` nested within the current object
+    pub fn join(mut self, segment: PathSegment) -> Self {
+        self.0.push(segment);
+        self
+    }
+
+    /// Returns the individual segments of this path from outermost to
+    /// innermost
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+}
+
+impl From<PathSegment> for ObjectPath {
+    fn from(value: PathSegment) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl fmt::Display for ObjectPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            if i != 0 {
+                f.write_str("/")?;
+            }
+            write!(f, "{segment}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A single glTF object in an [ObjectPath]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A scene and its optional name
+    Scene {
+        /// The glTF index of the scene
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A node and its optional name
+    Node {
+        /// The glTF index of the node
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A mesh and its optional name
+    Mesh {
+        /// The glTF index of the mesh
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A primitive by its index in the parent mesh
+    Primitive(usize),
+    /// A primitive attribute by its semantic name, e.g. `TEXCOORD_0`
+    Attribute(String),
+    /// An accessor by its glTF index
+    Accessor(usize),
+    /// An animation and its optional name
+    Animation {
+        /// The glTF index of the animation
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// An animation channel by its index in the parent animation
+    Channel(usize),
+    /// A buffer by its glTF index
+    Buffer(usize),
+    /// A buffer view by its glTF index
+    View(usize),
+    /// An image and its optional name
+    Image {
+        /// The glTF index of the image
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A material and its optional name
+    Material {
+        /// The glTF index of the material, [None] for the default material
+        index: Option<usize>,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A texture and its optional name
+    Texture {
+        /// The glTF index of the texture
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A light and its optional name
+    Light {
+        /// The glTF index of the light
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A camera and its optional name
+    Camera {
+        /// The glTF index of the camera
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+    /// A skin and its optional name
+    Skin {
+        /// The glTF index of the skin
+        index: usize,
+        /// The optional user-defined name
+        name: Option<String>,
+    },
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn named(
+            f: &mut fmt::Formatter<'_>,
+            kind: &str,
+            index: usize,
+            name: &Option<String>,
+        ) -> fmt::Result {
+            match name {
+                Some(name) => write!(f, "{kind}{index} {name:?}"),
+                None => write!(f, "{kind}{index}"),
+            }
+        }
+
+        match self {
+            Self::Scene { index, name } => named(f, "Scene", *index, name),
+            Self::Node { index, name } => named(f, "Node", *index, name),
+            Self::Mesh { index, name } => named(f, "Mesh", *index, name),
+            Self::Primitive(index) => write!(f, "Primitive{index}"),
+            Self::Attribute(semantic) => f.write_str(semantic),
+            Self::Accessor(index) => write!(f, "Accessor{index}"),
+            Self::Animation { index, name } => named(f, "Animation", *index, name),
+            Self::Channel(index) => write!(f, "Channel{index}"),
+            Self::Buffer(index) => write!(f, "Buffer{index}"),
+            Self::View(index) => write!(f, "View{index}"),
+            Self::Image { index, name } => named(f, "Image", *index, name),
+            Self::Material {
+                index: Some(index),
+                name,
+            } => named(f, "Material", *index, name),
+            Self::Material { index: None, .. } => f.write_str("MaterialDefault"),
+            Self::Texture { index, name } => named(f, "Texture", *index, name),
+            Self::Light { index, name } => named(f, "Light", *index, name),
+            Self::Camera { index, name } => named(f, "Camera", *index, name),
+            Self::Skin { index, name } => named(f, "Skin", *index, name),
+        }
+    }
 }
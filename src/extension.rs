@@ -0,0 +1,405 @@
+//! Typed handling of glTF extensions
+//!
+//! Every [wrap](crate::wrap) type exposes the raw JSON of its extensions with
+//! `extension_value()`. A [GltfExtension] describes how to deserialize that
+//! JSON into a Rust type, which can then be requested with `extension::<T>()`
+//! on the wrapper. The typed value is deserialized once and cached by the
+//! [Document](crate::wrap::Document) for each glTF object.
+//!
+//! An [ExtensionHandler] can additionally contribute components or labeled
+//! assets while a [SimpleGltfTransformer](crate::simple::SimpleGltfTransformer)
+//! builds its scenes. Handlers are collected in an [ExtensionRegistry] which is
+//! provided by the transformer.
+use crate::{
+    error::{Error, Result},
+    wrap::{Node, Scene},
+};
+use bevy::{
+    asset::LoadContext,
+    ecs::world::{EntityWorldMut, World},
+    utils::HashMap,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    any::{Any, TypeId},
+    sync::{Arc, Mutex},
+};
+
+/// A glTF extension which can be deserialized from its JSON data
+///
+/// ```ignore
+/// #[derive(serde::Deserialize)]
+/// struct Variants {
+///     mappings: Vec<Mapping>,
+/// }
+///
+/// impl GltfExtension for Variants {
+///     const NAME: &'static str = "KHR_materials_variants";
+/// }
+///
+/// let variants = primitive.extension::<Variants>()?;
+/// ```
+pub trait GltfExtension: DeserializeOwned + Send + Sync + 'static {
+    /// The name of the extension as it appears in the glTF file, e.g.
+    /// `KHR_materials_variants`
+    const NAME: &'static str;
+}
+
+/// A handler which uses a [GltfExtension] while building scenes
+///
+/// Handlers are registered with an [ExtensionRegistry]. The name of the
+/// extension is automatically treated as supported when checking the
+/// `extensionsRequired` list of a glTF file.
+pub trait ExtensionHandler: Send + Sync + 'static {
+    /// The extension data handled by this [ExtensionHandler]
+    type Extension: GltfExtension;
+
+    /// Called for each [Node] entity which has this extension.
+    ///
+    /// Components can be added to the `entity` and labeled assets can be added
+    /// with the [LoadContext].
+    ///
+    /// ### Default Behavior
+    /// Does nothing.
+    fn process_node(
+        &self,
+        extension: &Self::Extension,
+        node: Node<'_>,
+        ctx: &mut LoadContext<'_>,
+        entity: &mut EntityWorldMut<'_>,
+    ) -> Result<()> {
+        let _ = (extension, node, ctx, entity);
+        Ok(())
+    }
+
+    /// Called once for each [Scene] which has this extension, after all of the
+    /// node entities have been spawned into the scene `world`.
+    ///
+    /// ### Default Behavior
+    /// Does nothing.
+    fn process_scene(
+        &self,
+        extension: &Self::Extension,
+        scene: Scene<'_>,
+        ctx: &mut LoadContext<'_>,
+        world: &mut World,
+    ) -> Result<()> {
+        let _ = (extension, scene, ctx, world);
+        Ok(())
+    }
+}
+
+/// Object safe version of [ExtensionHandler]
+trait ErasedHandler: Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+
+    fn process_node(
+        &self,
+        node: &Node<'_>,
+        ctx: &mut LoadContext<'_>,
+        entity: &mut EntityWorldMut<'_>,
+    ) -> Result<()>;
+
+    fn process_scene(
+        &self,
+        scene: &Scene<'_>,
+        ctx: &mut LoadContext<'_>,
+        world: &mut World,
+    ) -> Result<()>;
+}
+
+impl<H: ExtensionHandler> ErasedHandler for H {
+    fn name(&self) -> &'static str {
+        H::Extension::NAME
+    }
+
+    fn process_node(
+        &self,
+        node: &Node<'_>,
+        ctx: &mut LoadContext<'_>,
+        entity: &mut EntityWorldMut<'_>,
+    ) -> Result<()> {
+        match node.extension::<H::Extension>()? {
+            Some(ext) => ExtensionHandler::process_node(self, &ext, node.clone(), ctx, entity),
+            None => Ok(()),
+        }
+    }
+
+    fn process_scene(
+        &self,
+        scene: &Scene<'_>,
+        ctx: &mut LoadContext<'_>,
+        world: &mut World,
+    ) -> Result<()> {
+        match scene.extension::<H::Extension>()? {
+            Some(ext) => ExtensionHandler::process_scene(self, &ext, scene.clone(), ctx, world),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A collection of [ExtensionHandler]s provided by a transformer
+#[derive(Default)]
+pub struct ExtensionRegistry {
+    handlers: Vec<Box<dyn ErasedHandler>>,
+}
+
+impl ExtensionRegistry {
+    /// Create an empty [ExtensionRegistry]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an [ExtensionHandler] to this registry
+    pub fn register<H: ExtensionHandler>(&mut self, handler: H) -> &mut Self {
+        self.handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Builder style version of [ExtensionRegistry::register]
+    pub fn with<H: ExtensionHandler>(mut self, handler: H) -> Self {
+        self.register(handler);
+        self
+    }
+
+    /// Returns an [Iterator] over the names of the handled extensions
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.handlers.iter().map(|h| h.name())
+    }
+
+    /// Returns true if a handler is registered for the named extension
+    pub fn handles(&self, name: &str) -> bool {
+        self.names().any(|n| n == name)
+    }
+
+    /// Run all of the registered handlers for a [Node] entity
+    pub fn process_node(
+        &self,
+        node: &Node<'_>,
+        ctx: &mut LoadContext<'_>,
+        entity: &mut EntityWorldMut<'_>,
+    ) -> Result<()> {
+        for handler in &self.handlers {
+            handler
+                .process_node(node, ctx, entity)
+                .map_err(|e| e.context(node.path_segment()))?;
+        }
+        Ok(())
+    }
+
+    /// Run all of the registered handlers for a [Scene] world
+    pub fn process_scene(
+        &self,
+        scene: &Scene<'_>,
+        ctx: &mut LoadContext<'_>,
+        world: &mut World,
+    ) -> Result<()> {
+        for handler in &self.handlers {
+            handler
+                .process_scene(scene, ctx, world)
+                .map_err(|e| e.context(scene.path_segment()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Cache of deserialized [GltfExtension] values for a [Document](crate::wrap::Document)
+///
+/// Values are keyed by their type and the address of the extension JSON,
+/// which is unique per object and stable for the lifetime of the document.
+#[derive(Default)]
+pub(crate) struct ExtensionCache {
+    items: Mutex<HashMap<(TypeId, usize), Arc<dyn Any + Send + Sync>>>,
+}
+
+impl ExtensionCache {
+    /// Get the cached value for `T`, deserializing `value` on first access
+    pub(crate) fn get_or_parse<T: GltfExtension>(
+        &self,
+        value: Option<&Value>,
+    ) -> Result<Option<Arc<T>>> {
+        let Some(value) = value else {
+            return Ok(None);
+        };
+
+        let key = (TypeId::of::<T>(), value as *const Value as usize);
+        let mut items = self.items.lock().unwrap();
+
+        if let Some(cached) = items.get(&key) {
+            let cached = Arc::clone(cached)
+                .downcast::<T>()
+                .expect("Extension cache is keyed by TypeId");
+            return Ok(Some(cached));
+        }
+
+        let parsed = Arc::new(T::deserialize(value).map_err(|source| Error::Extension {
+            name: T::NAME,
+            source,
+        })?);
+        items.insert(key, parsed.clone());
+
+        Ok(Some(parsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_util::{gltf_file, run_document_test, DocumentTest},
+        wrap::Document,
+    };
+    use serde_json::json;
+
+    #[derive(serde::Deserialize)]
+    struct TestExtension {
+        value: u32,
+    }
+
+    impl GltfExtension for TestExtension {
+        const NAME: &'static str = "EXT_test";
+    }
+
+    /// Collects the `EXT_test` values of every object type
+    struct Collect;
+
+    impl DocumentTest for Collect {
+        type Output = Vec<(&'static str, Option<u32>)>;
+
+        async fn run(&self, doc: Document<'_>, _ctx: &mut LoadContext<'_>) -> Self::Output {
+            fn value(ext: Result<Option<Arc<TestExtension>>>) -> Option<u32> {
+                ext.unwrap().map(|ext| ext.value)
+            }
+
+            let mesh = doc.meshes().next().unwrap();
+            let primitive = mesh.primitives().next().unwrap();
+            let values = vec![
+                ("mesh", value(mesh.extension())),
+                ("primitive", value(primitive.extension())),
+                ("camera", value(doc.cameras().next().unwrap().extension())),
+                ("skin", value(doc.skins().next().unwrap().extension())),
+                (
+                    "animation",
+                    value(doc.animations().next().unwrap().extension()),
+                ),
+            ];
+            #[cfg(feature = "gltf_lights")]
+            let values = values
+                .into_iter()
+                .chain(
+                    doc.lights()
+                        .map(|light| ("light", value(light.extension()))),
+                )
+                .collect();
+
+            values
+        }
+    }
+
+    #[test]
+    fn extension_on_every_object() {
+        let ext = |value: u32| json!({ "EXT_test": { "value": value } });
+        let bin: Vec<u8> = [0.0f32, 1.0, 0.0, 0.0, 0.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+
+        let json = json!({
+            "extensionsUsed": ["EXT_test", "KHR_lights_punctual"],
+            "extensions": {
+                "KHR_lights_punctual": {
+                    "lights": [{ "type": "point" }, { "type": "point", "extensions": ext(6) }],
+                },
+            },
+            "bufferViews": [
+                { "buffer": 0, "byteLength": 8 },
+                { "buffer": 0, "byteOffset": 8, "byteLength": 12 },
+            ],
+            "accessors": [{ "bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR",
+                            "min": [0.0], "max": [1.0] },
+                          { "bufferView": 1, "componentType": 5126, "count": 1, "type": "VEC3",
+                            "min": [0.0, 0.0, 0.0], "max": [0.0, 0.0, 0.0] }],
+            "meshes": [{ "primitives": [{ "attributes": { "POSITION": 1 }, "extensions": ext(2) }] }],
+            "cameras": [{ "type": "perspective", "perspective": { "yfov": 1.0, "znear": 0.1 },
+                          "extensions": ext(3) }],
+            "skins": [{ "joints": [0], "extensions": ext(4) }],
+            "animations": [{
+                "channels": [{ "sampler": 0, "target": { "node": 0, "path": "weights" } }],
+                "samplers": [{ "input": 0, "output": 0 }],
+                "extensions": ext(5),
+            }],
+            "nodes": [{}],
+        });
+
+        let values = run_document_test(
+            "test.gltf",
+            &[("test.gltf", gltf_file(json, &bin))],
+            Collect,
+        );
+
+        let expected = [
+            ("mesh", None),
+            ("primitive", Some(2)),
+            ("camera", Some(3)),
+            ("skin", Some(4)),
+            ("animation", Some(5)),
+        ];
+        #[cfg(feature = "gltf_lights")]
+        let expected = [expected.as_slice(), &[("light", None), ("light", Some(6))]].concat();
+        assert_eq!(values, expected);
+    }
+
+    /// Collects the `EXT_test` value of every light and the diagnostics
+    #[cfg(feature = "gltf_lights")]
+    struct LightExtensions;
+
+    #[cfg(feature = "gltf_lights")]
+    impl DocumentTest for LightExtensions {
+        type Output = (Vec<Option<Value>>, Vec<crate::diagnostics::Diagnostic>);
+
+        async fn run(&self, doc: Document<'_>, _ctx: &mut LoadContext<'_>) -> Self::Output {
+            let values = doc
+                .lights()
+                .map(|light| light.extension_value("EXT_test").cloned())
+                .collect();
+            (values, doc.diagnostics().to_vec())
+        }
+    }
+
+    #[cfg(feature = "gltf_lights")]
+    #[test]
+    fn malformed_light_extensions() {
+        use crate::diagnostics::DiagnosticCode;
+
+        let json = json!({
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {
+                "KHR_lights_punctual": {
+                    "lights": [
+                        { "type": "point", "extensions": [1] },
+                        { "type": "point", "extensions": { "EXT_test": { "value": 1 } } },
+                    ],
+                },
+            },
+        });
+
+        let (values, diagnostics) = run_document_test(
+            "test.gltf",
+            &[("test.gltf", gltf_file(json, &[]))],
+            LightExtensions,
+        );
+
+        assert_eq!(values, [None, Some(json!({ "value": 1 }))]);
+        let invalid: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.code == DiagnosticCode::InvalidExtension)
+            .map(|d| d.path.clone())
+            .collect();
+        let light = crate::error::PathSegment::Light {
+            index: 0,
+            name: None,
+        };
+        assert_eq!(invalid, [light.into()]);
+    }
+}
@@ -83,24 +83,29 @@
 //! [AssetServer](bevy::asset:AssetServer) allowing bevy to evict not recently
 //! used data.
 //!
-//! ## Labeled loading of external sub-assets or unlabeled sub-assets
+//! ## Labeled loading of external sub-assets
 //!
 //! One weird point in glTF loading is that textures which use a buffer-view
 //! data source *must* become a labeled sub-asset, while textures that reference
-//! external files can either be labeled by using `load_direct()` or be anonymous
-//! with `load()`.
+//! external files could also be loaded as their own asset.
 //!
-//! This means depending on what the implementor has decided, only some of the
-//! textures in a glTF file may be accessible as a labeled asset. It would be
-//! nice if there were a `load_as_labeled()` or `add_unlabeled_asset()` to unify
-//! handling of these types of assets.
+//! To keep texture handling consistent, [Texture::load_labeled](wrap::Texture::load_labeled)
+//! turns every glTF texture into a labeled sub-asset, e.g. `Texture3`. For
+//! external files this means the image is loaded immediately and copied into
+//! the glTF asset, rather than sharing the asset of the referenced file. It
+//! would be nice if there were a `load_as_labeled()` to unify handling of
+//! these types of assets.
 //!
 
 #![warn(missing_docs)]
 #![allow(clippy::result_large_err)]
 pub mod data;
+pub mod diagnostics;
 pub mod error;
+pub mod extension;
 pub mod simple;
+#[cfg(test)]
+mod test_util;
 mod util;
 pub mod wrap;
 
@@ -123,7 +128,7 @@ pub trait GltfTransformer: Send + Sync + 'static {
         + serde::Serialize
         + for<'a> serde::Deserialize<'a>;
     /// The type of [error](`std::error::Error`) which could be encountered by this transformer.
-    type Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>;
+    type Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>> + From<error::Error>;
 
     /// Asynchronously loads AssetLoader::Asset (and any other labeled assets) from glTF [Document].
     fn load<'a>(
@@ -144,6 +149,29 @@ pub trait GltfTransformer: Send + Sync + 'static {
     fn extensions(&self) -> &[&str] {
         &[]
     }
+
+    /// Returns a list of glTF extensions supported by this transformer.
+    ///
+    /// Loading fails with [Error::UnsupportedExtension](error::Error::UnsupportedExtension)
+    /// if a glTF file lists an extension in `extensionsRequired` which is not
+    /// in this list. Extensions which are only used are loaded normally.
+    ///
+    /// Defaults to the extensions interpreted by the [Document] wrappers,
+    /// see [SUPPORTED_EXTENSIONS](wrap::SUPPORTED_EXTENSIONS).
+    fn supported_gltf_extensions(&self) -> &[&str] {
+        wrap::SUPPORTED_EXTENSIONS
+    }
+
+    /// Returns the [ExtensionRegistry](extension::ExtensionRegistry) of
+    /// handlers used by this transformer.
+    ///
+    /// The extensions of all registered handlers are treated as supported in
+    /// addition to [GltfTransformer::supported_gltf_extensions].
+    ///
+    /// Defaults to no registered handlers.
+    fn extension_handlers(&self) -> Option<&extension::ExtensionRegistry> {
+        None
+    }
 }
 
 /// An [AssetLoader] which loads glTF files with a
@@ -166,39 +194,26 @@ impl<T: GltfTransformer> AssetLoader for GltfTransformLoader<T> {
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut buffer = Vec::new();
-        reader.read_to_end(&mut buffer).await.unwrap();
-        let buffer = buffer.into_boxed_slice();
-
-        let (document, blob) = match gltf::Glb::from_slice(&buffer) {
-            Ok(glb) => {
-                let document =
-                    gltf::Document::from_json(gltf::json::Root::from_slice(&glb.json).unwrap())
-                        .unwrap();
-                (document, glb.bin)
-            }
-            Err(_) => {
-                let gltf = gltf::Gltf::from_slice(&buffer).unwrap();
-                (
-                    gltf.document,
-                    None, /* Always none when loading text GLTF */
-                )
-            }
-        };
+        reader
+            .read_to_end(&mut buffer)
+            .await
+            .map_err(error::Error::from)?;
+
+        let (document, cache) = parse_gltf(buffer.into_boxed_slice())?;
 
-        // Buffer cache takes ownership of the whole document
-        let cache = if let Some(blob) = blob {
-            match blob {
-                Cow::Owned(o) => Cache::new(OwningSlice::new_complete(o.into_boxed_slice())),
-                Cow::Borrowed(s) => {
-                    let offset = OwningSlice::find_offset(&buffer, s)
-                    .expect("Borrowed glTF data chunk was not part of the original buffer, or exceeded the buffer length");
-                    let slice_len = s.len();
-                    Cache::new(unsafe { OwningSlice::new(buffer, offset, slice_len) })
-                }
-            }
-        } else {
-            util::Cache::empty()
+        let handlers = self.0.extension_handlers();
+        let supported = |ext: &str| {
+            self.0.supported_gltf_extensions().contains(&ext)
+                || handlers.is_some_and(|h| h.handles(ext))
         };
+        if let Some(ext) = document.extensions_required().find(|ext| !supported(ext)) {
+            return Err(error::Error::UnsupportedExtension(ext.to_string()).into());
+        }
+
+        let diagnostics = diagnostics::Diagnostics::default();
+
+        #[cfg(feature = "gltf_lights")]
+        let (document, light_extensions) = wrap::light::parse_extensions(document, &diagnostics);
 
         let paths: OnceLock<HashMap<usize, (usize, Vec<Name>)>> = OnceLock::new();
 
@@ -206,14 +221,70 @@ impl<T: GltfTransformer> AssetLoader for GltfTransformLoader<T> {
             doc: document,
             cache,
             paths,
+            diagnostics,
+            extensions: Default::default(),
+            textures: Default::default(),
+            #[cfg(feature = "gltf_lights")]
+            light_extensions,
         };
 
         let doc = wrap::Document { inner: &inner };
 
+        // Optional extensions are ignored, but record them for the user
+        for ext in doc.extensions_used().filter(|ext| !supported(ext)) {
+            doc.diagnostics().report(diagnostics::Diagnostic::new(
+                error::ObjectPath::default(),
+                diagnostics::Severity::Info,
+                diagnostics::DiagnosticCode::UnsupportedExtension,
+                format!("extension `{ext}` is not supported and will be ignored"),
+            ));
+        }
+
         T::load(&self.0, doc, settings, load_context).await
     }
 }
 
+/// Parses a `.gltf` or `.glb` file into a [gltf::Document] and a [Cache]
+/// holding the binary chunk of a `.glb` file.
+///
+/// Any malformed input is reported as an [Error](error::Error) rather than
+/// a panic.
+pub(crate) fn parse_gltf(buffer: Box<[u8]>) -> error::Result<(gltf::Document, Cache)> {
+    // Text glTF files never contain a binary chunk
+    if !buffer.starts_with(b"glTF") {
+        let gltf = gltf::Gltf::from_slice(&buffer)?;
+        return Ok((gltf.document, Cache::empty()));
+    }
+
+    // The `gltf` crate subtracts the header size from the declared file
+    // length without checking it, so reject undersized lengths up front.
+    const GLB_HEADER_SIZE: u32 = 12;
+    if let Some(length) = buffer.get(8..12) {
+        let length = u32::from_le_bytes(length.try_into().unwrap());
+        if length < GLB_HEADER_SIZE {
+            return Err(error::Error::GlbHeaderLength(length));
+        }
+    }
+
+    let glb = gltf::Glb::from_slice(&buffer)?;
+    let root = gltf::json::Root::from_slice(&glb.json).map_err(gltf::Error::from)?;
+    let document = gltf::Document::from_json(root)?;
+
+    // Buffer cache takes ownership of the whole document
+    let cache = match glb.bin {
+        Some(Cow::Owned(o)) => Cache::new(OwningSlice::new_complete(o.into_boxed_slice())),
+        Some(Cow::Borrowed(s)) => {
+            let offset =
+                OwningSlice::find_offset(&buffer, s).ok_or(error::Error::GlbChunkOffset)?;
+            let slice_len = s.len();
+            Cache::new(unsafe { OwningSlice::new(buffer, offset, slice_len) })
+        }
+        None => Cache::empty(),
+    };
+
+    Ok((document, cache))
+}
+
 // async fn load_gltf<T: GltfTransformer>(
 //     t: &T,
 //     reader: &mut dyn bevy::asset::io::Reader,
@@ -221,3 +292,135 @@ impl<T: GltfTransformer> AssetLoader for GltfTransformLoader<T> {
 //     load_context: &mut bevy::asset::LoadContext<'_>,
 // ) -> Result<T::Asset, T::Error> {
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error::Error;
+    use util::OwningSlice;
+
+    const JSON: &[u8] = br#"{"asset":{"version":"2.0"}}"#;
+
+    /// Builds a GLB file with a single JSON chunk, optionally overriding the
+    /// declared file and chunk lengths
+    fn glb(json: &[u8], length: Option<u32>, json_length: Option<u32>) -> Box<[u8]> {
+        let mut json = json.to_vec();
+        json.resize(json.len().next_multiple_of(4), b' ');
+
+        let total = 12 + 8 + json.len() as u32;
+        let mut data = Vec::new();
+        data.extend_from_slice(b"glTF");
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&length.unwrap_or(total).to_le_bytes());
+        data.extend_from_slice(&json_length.unwrap_or(json.len() as u32).to_le_bytes());
+        data.extend_from_slice(b"JSON");
+        data.extend_from_slice(&json);
+        data.into_boxed_slice()
+    }
+
+    #[test]
+    fn valid_glb() {
+        assert!(parse_gltf(glb(JSON, None, None)).is_ok());
+    }
+
+    #[test]
+    fn truncated_glb_header() {
+        // Declared file length smaller than the header
+        let result = parse_gltf(glb(JSON, Some(8), None));
+        assert!(matches!(result, Err(Error::GlbHeaderLength(8))));
+
+        // File ends inside the header
+        let result = parse_gltf(b"glTF\x02\x00\x00".to_vec().into_boxed_slice());
+        assert!(matches!(result, Err(Error::Gltf(gltf::Error::Binary(_)))));
+    }
+
+    #[test]
+    fn bad_json() {
+        let result = parse_gltf(b"{\"asset\": ".to_vec().into_boxed_slice());
+        assert!(matches!(
+            result,
+            Err(Error::Gltf(gltf::Error::Deserialize(_)))
+        ));
+
+        let result = parse_gltf(glb(b"{\"asset\": ", None, None));
+        assert!(matches!(
+            result,
+            Err(Error::Gltf(gltf::Error::Deserialize(_)))
+        ));
+    }
+
+    #[test]
+    fn out_of_range_chunk_length() {
+        let result = parse_gltf(glb(JSON, None, Some(u32::MAX - 3)));
+        assert!(matches!(
+            result,
+            Err(Error::Gltf(gltf::Error::Binary(
+                gltf::binary::Error::ChunkLength { .. }
+            )))
+        ));
+
+        let result = parse_gltf(glb(JSON, Some(u32::MAX), None));
+        assert!(matches!(
+            result,
+            Err(Error::Gltf(gltf::Error::Binary(
+                gltf::binary::Error::Length { .. }
+            )))
+        ));
+    }
+
+    #[cfg(feature = "gltf_pbr")]
+    struct Loads;
+
+    #[cfg(feature = "gltf_pbr")]
+    impl test_util::DocumentTest for Loads {
+        type Output = ();
+
+        async fn run(&self, _doc: Document<'_>, _ctx: &mut LoadContext<'_>) {}
+    }
+
+    #[cfg(feature = "gltf_pbr")]
+    #[test]
+    fn required_pbr_extensions() {
+        let json = serde_json::json!({
+            "extensionsUsed": [
+                "KHR_materials_transmission",
+                "KHR_materials_ior",
+                "KHR_materials_emissive_strength",
+                "KHR_materials_unlit",
+                "KHR_materials_volume",
+            ],
+            "extensionsRequired": [
+                "KHR_materials_transmission",
+                "KHR_materials_ior",
+                "KHR_materials_emissive_strength",
+                "KHR_materials_unlit",
+                "KHR_materials_volume",
+            ],
+        });
+        let file = test_util::gltf_file(json, &[]);
+        test_util::run_document_test("test.gltf", &[("test.gltf", file)], Loads);
+    }
+
+    #[cfg(feature = "gltf_pbr")]
+    #[test]
+    #[should_panic(expected = "KHR_materials_pbrSpecularGlossiness")]
+    fn unsupported_pbr_extension() {
+        let json = serde_json::json!({
+            "extensionsUsed": ["KHR_materials_pbrSpecularGlossiness"],
+            "extensionsRequired": ["KHR_materials_pbrSpecularGlossiness"],
+        });
+        let file = test_util::gltf_file(json, &[]);
+        test_util::run_document_test("test.gltf", &[("test.gltf", file)], Loads);
+    }
+
+    #[test]
+    fn find_offset_bounds() {
+        let root = [0u8; 16];
+        assert_eq!(OwningSlice::find_offset(&root, &root[4..8]), Some(4));
+        assert_eq!(OwningSlice::find_offset(&root, &root[16..]), Some(16));
+
+        // Slices starting before or ending after the root are rejected
+        assert_eq!(OwningSlice::find_offset(&root[4..], &root[..8]), None);
+        assert_eq!(OwningSlice::find_offset(&root[..8], &root[4..12]), None);
+    }
+}
@@ -1,26 +1,48 @@
 //! Structured glTF asset loading with simple transformation of Material and
 //! Mesh assets
 pub mod gltf;
+mod reflect;
 
+#[cfg(feature = "animation")]
+use bevy::{
+    animation::{
+        graph::{AnimationGraph, AnimationGraphHandle},
+        AnimationPlayer, AnimationTarget, AnimationTargetId,
+    },
+    utils::hashbrown::HashSet,
+};
 use bevy::{
     app::Plugin,
     asset::{Asset, AssetApp, Handle, LoadContext},
+    core::Name,
     ecs::{
+        bundle::Bundle,
         entity::Entity,
-        world::{FromWorld, World},
+        reflect::AppTypeRegistry,
+        world::{EntityWorldMut, FromWorld, World},
+    },
+    hierarchy::{BuildChildren, ChildBuild, Children},
+    render::{
+        mesh::{
+            morph::{MeshMorphWeights, MorphWeights},
+            skinning::SkinnedMesh,
+        },
+        view::Visibility,
     },
-    hierarchy::{BuildChildren, Children},
-    log::warn,
-    render::view::Visibility,
     scene::Scene as BevyScene,
     tasks::futures_lite::prelude::Future,
+    transform::components::Transform,
     utils::hashbrown::HashMap,
 };
 
 use crate::{
-    wrap::{scene::traversal::FilteredDepthFirst, Material, Mesh, Node, Primitive, Scene},
+    diagnostics::{Diagnostic, DiagnosticCode},
+    error::{Error, ObjectPath},
+    extension::ExtensionRegistry,
+    wrap::{scene::traversal::FilteredDepthFirst, Material, Mesh, Node, Primitive, Scene, Skin},
     GltfTransformLoader, GltfTransformer,
 };
+use serde::{Deserialize, Serialize};
 
 /// Plugin to add a new [SimpleGltfTransformer] and its associated
 /// [Gltf](gltf::Gltf) type to an app
@@ -31,15 +53,242 @@ where
     S: SimpleGltfTransformer,
 {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.register_asset_loader(GltfTransformLoader(S::from_plugin(&self.0)))
+        let mut transformer = S::from_plugin(&self.0);
+        if let Some(registry) = app.world().get_resource::<AppTypeRegistry>() {
+            transformer.set_type_registry(registry.clone());
+        }
+
+        app.register_asset_loader(GltfTransformLoader(transformer))
             .init_asset::<gltf::Gltf<S::Mesh, S::Material>>()
             .init_asset::<gltf::GltfNode<S::Mesh, S::Material>>()
             .init_asset::<gltf::GltfMesh<S::Mesh, S::Material>>()
-            .init_asset::<gltf::GltfPrimitive<S::Mesh, S::Material>>();
+            .init_asset::<gltf::GltfPrimitive<S::Mesh, S::Material>>()
+            .register_type::<gltf::GltfExtras>()
+            .register_type::<gltf::GltfSceneExtras>()
+            .register_type::<gltf::GltfMeshExtras>()
+            .register_type::<gltf::GltfMaterialExtras>();
         // TODO: Systems to allow node and mesh loading?
     }
 }
 
+/// Options that control the standard loading behavior of a
+/// [SimpleGltfTransformer]
+///
+/// These are provided per-load by [SimpleGltfTransformer::load_options] and
+/// are intended to be stored as part of the transformer's
+/// [LoadSettings](SimpleGltfTransformer::LoadSettings).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadOptions {
+    /// Attach the [Diagnostics](crate::diagnostics::Diagnostics) recorded
+    /// during the load to the [Gltf](gltf::Gltf) asset.
+    pub attach_diagnostics: bool,
+    /// Insert reflected components stored under this key of each node's
+    /// extras on the node entity.
+    ///
+    /// Requires [SimpleGltfTransformer::type_registry] to provide the app's
+    /// type registry, otherwise a diagnostic is reported and no components
+    /// are inserted. Entries map type paths to component values in the
+    /// same format as `bevy_scene`.
+    pub reflect_components_key: Option<String>,
+    /// How [Name] components are generated for nodes without a user-defined
+    /// name
+    pub unnamed_nodes: UnnamedNodeNames,
+    /// Spawn cameras on the entities of nodes which reference a glTF camera
+    pub cameras: CameraSpawn,
+    /// How `KHR_lights_punctual` lights are spawned
+    #[cfg(feature = "bevy_3d")]
+    pub lights: LightOptions,
+    /// Generate an [AnimationGraph](bevy::animation::graph::AnimationGraph)
+    /// asset for each scene, with one clip node for each animation that
+    /// targets the scene
+    #[cfg(feature = "animation")]
+    pub animation_graphs: bool,
+}
+
+/// Options for spawning `KHR_lights_punctual` lights in scenes
+#[cfg(feature = "bevy_3d")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LightOptions {
+    /// Spawn lights as children of the node entities which reference them
+    pub spawn: bool,
+    /// Multiplier applied to the converted intensity of every light
+    pub intensity_scale: f32,
+    /// Enable shadows for all spawned lights, glTF lights have no shadow
+    /// setting of their own
+    pub shadows_enabled: bool,
+}
+
+#[cfg(feature = "bevy_3d")]
+impl Default for LightOptions {
+    fn default() -> Self {
+        Self {
+            spawn: true,
+            intensity_scale: 1.0,
+            shadows_enabled: false,
+        }
+    }
+}
+
+#[cfg(feature = "bevy_3d")]
+impl LightOptions {
+    /// Spawns the light of `node` as a child of `entity`
+    ///
+    /// glTF lights and Bevy lights both shine along the local `-Z` axis, so
+    /// the light entity uses an identity [Transform] to inherit the node's
+    /// orientation, as `bevy_gltf` does.
+    fn spawn(&self, node: &Node, entity: &mut EntityWorldMut) {
+        use crate::wrap::light::LightKind;
+
+        let Some(light) = node.light().filter(|_| self.spawn) else {
+            return;
+        };
+
+        let mut light_entity = None;
+        entity.with_children(|parent| {
+            let mut child = match light.as_bevy_light() {
+                LightKind::Directional(mut l) => {
+                    l.illuminance *= self.intensity_scale;
+                    l.shadows_enabled = self.shadows_enabled;
+                    parent.spawn(l)
+                }
+                LightKind::Point(mut l) => {
+                    l.intensity *= self.intensity_scale;
+                    l.shadows_enabled = self.shadows_enabled;
+                    parent.spawn(l)
+                }
+                LightKind::Spot(mut l) => {
+                    l.intensity *= self.intensity_scale;
+                    l.shadows_enabled = self.shadows_enabled;
+                    parent.spawn(l)
+                }
+            };
+            child.insert(Transform::default());
+            light_entity = Some(child.id());
+        });
+
+        if let Some(light_entity) = light_entity {
+            entity.world_scope(|world| {
+                let mut child = world.entity_mut(light_entity);
+                if let Some(name) = light.name() {
+                    child.insert(Name::new(String::from(name)));
+                }
+                if let Some(extras) = gltf::GltfExtras::from_raw(light.extras()) {
+                    child.insert(extras);
+                }
+            });
+        }
+    }
+}
+
+/// Which kind of Bevy camera to spawn for glTF cameras
+///
+/// Only the first camera spawned in each scene is active.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraSpawn {
+    /// Do not spawn cameras
+    #[default]
+    None,
+    /// Spawn a [Camera3d](bevy::core_pipeline::core_3d::Camera3d) with the
+    /// [projection](crate::wrap::Camera::projection) of the glTF camera
+    #[cfg(feature = "bevy_3d")]
+    Camera3d,
+    /// Spawn a [Camera2d](bevy::core_pipeline::core_2d::Camera2d) with the
+    /// [projection](crate::wrap::Camera::projection_2d) of an orthographic
+    /// glTF camera
+    #[cfg(feature = "bevy_2d")]
+    Camera2d,
+}
+
+impl CameraSpawn {
+    /// Inserts the camera components for `node` on `entity`
+    ///
+    /// Returns true if a camera was inserted.
+    fn insert(&self, node: &Node, entity: &mut EntityWorldMut, is_active: bool) -> bool {
+        let Some(camera) = node.camera() else {
+            return false;
+        };
+        // Squash lint warnings when no camera features are enabled
+        let _ = (&camera, &entity, is_active);
+
+        match self {
+            Self::None => false,
+            #[cfg(feature = "bevy_3d")]
+            Self::Camera3d => {
+                entity.insert((
+                    bevy::core_pipeline::core_3d::Camera3d::default(),
+                    bevy::render::camera::Camera {
+                        is_active,
+                        ..Default::default()
+                    },
+                    bevy::render::camera::Projection::from(camera.projection()),
+                ));
+                true
+            }
+            #[cfg(feature = "bevy_2d")]
+            Self::Camera2d => {
+                entity.insert((
+                    bevy::core_pipeline::core_2d::Camera2d,
+                    bevy::render::camera::Camera {
+                        is_active,
+                        ..Default::default()
+                    },
+                ));
+                if let crate::wrap::camera::Projection::Orthographic(ortho) = camera.projection_2d()
+                {
+                    entity.insert(ortho);
+                }
+                true
+            }
+        }
+    }
+}
+
+/// Naming policy for scene entities of glTF nodes without a name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnnamedNodeNames {
+    /// Generate a name from the glTF index, e.g. `Node23`
+    ///
+    /// This matches the names used for the [Node::path].
+    #[default]
+    Index,
+    /// Use the name of the node's mesh if present, otherwise fall back to
+    /// [UnnamedNodeNames::Index]
+    MeshName,
+    /// Do not insert a [Name] component for unnamed nodes
+    Omit,
+}
+
+impl UnnamedNodeNames {
+    /// Returns the [Name] to insert on the entity for `node`
+    pub fn name_for(&self, node: &Node) -> Option<Name> {
+        if let Some(name) = node.name() {
+            return Some(Name::new(String::from(name)));
+        }
+
+        let index_name = || Name::new(format!("Node{}", node.index()));
+        match self {
+            Self::Index => Some(index_name()),
+            Self::MeshName => Some(
+                node.mesh()
+                    .and_then(|mesh| mesh.name().map(|name| Name::new(String::from(name))))
+                    .unwrap_or_else(index_name),
+            ),
+            Self::Omit => None,
+        }
+    }
+}
+
+/// The handles and extras needed to spawn a primitive entity in a scene
+struct ScenePrimitive<Mesh: Asset, Mat: Asset> {
+    mesh: Handle<Mesh>,
+    material: Option<Handle<Mat>>,
+    extras: Option<gltf::GltfExtras>,
+    mat_extras: Option<gltf::GltfMaterialExtras>,
+    morph_targets: usize,
+}
+
 /// A simple interface to implement a [GltfTransformer]
 ///
 /// This allows customizing the Material and Mesh types that get loaded, while
@@ -62,6 +311,25 @@ pub trait SimpleGltfTransformer: Send + Sync + 'static {
     /// Construct a this loader from settings stored in the [SimpleGltfPlugin]
     fn from_plugin(setttings: &Self::PluginSettings) -> Self;
 
+    /// Called by the [SimpleGltfPlugin] with the app's [AppTypeRegistry]
+    /// after constructing this loader.
+    ///
+    /// ### Default Behavior
+    /// The registry is ignored. Store it and return it from
+    /// [SimpleGltfTransformer::type_registry] to support
+    /// [LoadOptions::reflect_components_key].
+    fn set_type_registry(&mut self, registry: AppTypeRegistry) {
+        let _ = registry;
+    }
+
+    /// Returns the [AppTypeRegistry] used to deserialize reflected components
+    ///
+    /// ### Default Behavior
+    /// Returns [None], which disables reflected components in node extras.
+    fn type_registry(&self) -> Option<&AppTypeRegistry> {
+        None
+    }
+
     /// Optionally process a default material for primitives which do not
     /// have a recorded material.
     ///
@@ -109,6 +377,42 @@ pub trait SimpleGltfTransformer: Send + Sync + 'static {
         primitive: Primitive<'a>,
     ) -> impl Future<Output = Result<Option<Self::Mesh>, Self::Error>> + Send;
 
+    /// Process the entity spawned for a [Node] while building a [Scene]
+    ///
+    /// This is called once the node's own components, primitive entities and
+    /// children have been attached to the `entity`, and can be used to add
+    /// gameplay components such as colliders or spawn points.
+    ///
+    /// Components which depend on other nodes of the scene are inserted after
+    /// every node has been processed, so [SkinnedMesh] on the primitive
+    /// entities and (feature "animation" only) [AnimationTarget] and
+    /// [AnimationPlayer] are not yet present when this is called.
+    ///
+    /// ### Default Behavior
+    /// Does nothing.
+    fn process_node<'a>(
+        &'a self,
+        ctx: &'a mut LoadContext<'_>,
+        settings: &'a Self::LoadSettings,
+        scene: Scene<'a>,
+        node: Node<'a>,
+        entity: &'a mut EntityWorldMut<'_>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async move {
+            let _ = (ctx, settings, scene, node, entity);
+            Ok(())
+        }
+    }
+
+    /// Returns the [LoadOptions] to use for a load with the given settings
+    ///
+    /// ### Default Behavior
+    /// By default this returns [LoadOptions::default()].
+    fn load_options(&self, settings: &Self::LoadSettings) -> LoadOptions {
+        let _ = settings;
+        LoadOptions::default()
+    }
+
     /// Optionally filters out [Nodes](Node) from a [Scene] tree
     ///
     /// For any [Node] that this function return's `false`, that node and
@@ -122,6 +426,31 @@ pub trait SimpleGltfTransformer: Send + Sync + 'static {
         true
     }
 
+    /// Returns the components which render the mesh of a
+    /// [GltfPrimitive](gltf::GltfPrimitive), e.g. `Mesh3d(mesh)`.
+    ///
+    /// These are inserted on a child entity of the node entity for every
+    /// primitive of the node's mesh when building scenes.
+    ///
+    /// ### Default Behavior
+    /// No components are inserted.
+    fn mesh_components(&self, mesh: Handle<Self::Mesh>) -> impl Bundle {
+        let _ = mesh;
+    }
+
+    /// Returns the components which apply the material of a
+    /// [GltfPrimitive](gltf::GltfPrimitive), e.g. `MeshMaterial3d(material)`.
+    ///
+    /// These are inserted on the same entity as
+    /// [SimpleGltfTransformer::mesh_components] when the primitive has a
+    /// material.
+    ///
+    /// ### Default Behavior
+    /// No components are inserted.
+    fn material_components(&self, material: Handle<Self::Material>) -> impl Bundle {
+        let _ = material;
+    }
+
     /// Returns a list of extensions supported by this AssetLoader, without the preceding dot.
     /// Note that users of this AssetLoader may choose to load files with a non-matching extension.
     ///
@@ -133,6 +462,29 @@ pub trait SimpleGltfTransformer: Send + Sync + 'static {
     fn extensions(&self) -> &[&str] {
         &[]
     }
+
+    /// Returns a list of glTF extensions supported by this transformer.
+    ///
+    /// Loading fails with [Error::UnsupportedExtension](crate::error::Error::UnsupportedExtension)
+    /// if a glTF file lists an extension in `extensionsRequired` which is not
+    /// in this list. Extensions which are only used are loaded normally.
+    ///
+    /// Defaults to the extensions interpreted by the [Document](crate::wrap::Document) wrappers,
+    /// see [SUPPORTED_EXTENSIONS](crate::wrap::SUPPORTED_EXTENSIONS).
+    fn supported_gltf_extensions(&self) -> &[&str] {
+        crate::wrap::SUPPORTED_EXTENSIONS
+    }
+
+    /// Returns the [ExtensionRegistry] of handlers which are run for each
+    /// node and scene while building scenes.
+    ///
+    /// The extensions of all registered handlers are treated as supported in
+    /// addition to [SimpleGltfTransformer::supported_gltf_extensions].
+    ///
+    /// Defaults to no registered handlers.
+    fn extension_handlers(&self) -> Option<&ExtensionRegistry> {
+        None
+    }
 }
 
 impl<S> GltfTransformer for S
@@ -149,17 +501,51 @@ where
      * Loading takes several steps:
      *  1. All materials are loaded with [SimpleGltfTransformer::process_material]
      *    * Each material will determine the appropriate settings for textures that
-     *      it uses. This may lead to duplicate loading of textures. Future
-     *      implementations will provide a cache of preloaded textures.
+     *      it uses. Textures loaded with [Texture::load_cached](crate::wrap::Texture::load_cached)
+     *      are shared between materials using the same settings.
      *  2. All meshes are loaded and [GltfPrimitive](gltf::GltfPrimitive) assets
      *     are created with the mesh data and associated material. If the glTF
      *     default material is specified, the [SimpleGltfTransformer::default_material]
      *     function will be called and the result will be cached for future use.
-     *  3. Scenes will be processed and an entity hierarchy will be constructed.
-     *    * Nodes which do not have a user specified name will have a name generated
-     *      based on their glTF index, e.g. `Node23`.
-     *  4. (Feature "animations" only) Animations will be loaded as
+     *  3. Every node is loaded as a labeled [GltfNode](gltf::GltfNode) asset,
+     *     e.g. `Node23`, referencing its mesh and child nodes.
+     *    * The inverse bind matrices of every skin are loaded as labeled
+     *      [SkinnedMeshInverseBindposes](bevy::render::mesh::skinning::SkinnedMeshInverseBindposes)
+     *      assets, e.g. `InverseBindMatrices2`.
+     *  4. (Feature "animation" only) Animations will be loaded as
      *     [AnimationClips](bevy::animation::AnimationClip).
+     *  5. Scenes will be processed and an entity hierarchy will be constructed.
+     *    * Every node entity gets a [Name](bevy::core::Name). Nodes which do not
+     *      have a user specified name will have a name generated according
+     *      to [LoadOptions::unnamed_nodes], by default based on their glTF
+     *      index, e.g. `Node23`.
+     *    * Each primitive of a node's mesh is spawned as a child entity with the
+     *      components from [SimpleGltfTransformer::mesh_components] and
+     *      [SimpleGltfTransformer::material_components].
+     *    * Nodes with a morph target mesh get a [MorphWeights] component and
+     *      their morphed primitive entities a [MeshMorphWeights] component.
+     *    * Primitive entities of nodes with a skin get a [SkinnedMesh] component
+     *      once all of the skin's joint entities have been spawned.
+     *    * If enabled by [LoadOptions::cameras], cameras are spawned for nodes
+     *      with a glTF camera.
+     *    * (Feature "bevy_3d" only) Lights are spawned as child entities of their
+     *      nodes according to [LoadOptions::lights].
+     *    * Node, scene and mesh extras are attached to the node entities as
+     *      [GltfExtras](gltf::GltfExtras) and related components. Primitive
+     *      and material extras are attached to the primitive entities.
+     *    * If [LoadOptions::reflect_components_key] is set, reflected components
+     *      are deserialized from the node extras and inserted.
+     *    * The [ExtensionRegistry] handlers are run for each node entity and
+     *      then for each scene.
+     *    * [SimpleGltfTransformer::process_node] is called for each node entity
+     *      after the extension handlers, before skins and animation targets
+     *      are resolved.
+     *    * (Feature "animation" only) Nodes targeted by an animation get an
+     *      [AnimationTarget] and the root node of their tree an [AnimationPlayer].
+     *      With [LoadOptions::animation_graphs] an [AnimationGraph] is added for
+     *      each scene, e.g. `AnimationGraph0`, and attached to the players.
+     *  6. If requested by the [LoadOptions] the recorded diagnostics are
+     *     attached to the [Gltf](gltf::Gltf) asset.
      */
     async fn load<'a>(
         &'a self,
@@ -167,13 +553,11 @@ where
         settings: &'a Self::Settings,
         ctx: &'a mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
+        let options = self.load_options(settings);
+
         /*
          * 1) Process materials
-         *
-         * This may cause duplicate loads of textures, perhaps there is a
-         * better way to provide cached textures to the loader.
          */
-        // FIXME: Look into texture caching
         let mut materials = Vec::new();
         let mut named_materials = HashMap::new();
 
@@ -198,15 +582,17 @@ where
          */
         let mut meshes = Vec::new();
         let mut named_meshes = HashMap::new();
+        let mut scene_primitives = Vec::new();
 
         for mesh in document.meshes() {
             let mut mesh_ctx = ctx.begin_labeled_asset();
             let mut mesh_asset = gltf::GltfMesh {
                 primitives: Vec::new(),
-                extras: None,
+                extras: gltf::GltfExtras::from_raw(mesh.extras()),
             };
             let index = mesh.index();
             let name = mesh.name();
+            let mut mesh_primitives = Vec::new();
 
             for primitive in mesh.primitives() {
                 let prim_index = primitive.index();
@@ -235,6 +621,12 @@ where
                     }
                 };
 
+                let morph_targets = primitive.morph_targets().len();
+                let extras = gltf::GltfExtras::from_raw(primitive.extras());
+                let mat_extras = gltf::GltfExtras::from_raw(primitive.material().extras());
+                let scene_mat_extras =
+                    gltf::GltfMaterialExtras::from_raw(primitive.material().extras());
+
                 let Some(prim) = self
                     .process_primitive(&mut mesh_ctx, settings, mesh.clone(), primitive)
                     .await?
@@ -244,13 +636,21 @@ where
                 let handle =
                     mesh_ctx.add_labeled_asset(format!("Mesh{index}/Primitive{prim_index}"), prim);
 
+                mesh_primitives.push(ScenePrimitive {
+                    mesh: handle.clone(),
+                    material: mat_handle.clone(),
+                    extras: extras.clone(),
+                    mat_extras: scene_mat_extras,
+                    morph_targets,
+                });
                 mesh_asset.primitives.push(gltf::GltfPrimitive {
                     mesh: handle,
-                    extras: None,
+                    extras,
                     material: mat_handle,
-                    mat_extras: None,
+                    mat_extras,
                 });
             }
+            scene_primitives.push(mesh_primitives);
 
             let mesh_asset = mesh_ctx.finish(mesh_asset, None);
             let mesh_handle = ctx.add_loaded_labeled_asset(format!("Mesh{index}"), mesh_asset);
@@ -261,14 +661,60 @@ where
             }
         }
 
+        /*
+         * 3) Process nodes
+         *
+         * Children reference the labels of other nodes so they can be
+         * created in any order.
+         */
+        let mut nodes = Vec::with_capacity(document.nodes().len());
+        let mut named_nodes = HashMap::new();
+
+        for node in document.nodes() {
+            let node_asset = gltf::GltfNode {
+                children: node
+                    .children()
+                    .map(|child| ctx.get_label_handle(format!("Node{}", child.index())))
+                    .collect(),
+                mesh: node
+                    .mesh()
+                    .and_then(|mesh| meshes.get(mesh.index()).cloned()),
+                camera: node.camera().map(|camera| camera.projection()),
+                #[cfg(feature = "bevy_3d")]
+                light: node.light().map(|light| light.as_bevy_light()),
+                transform: node.transform(),
+                extras: gltf::GltfExtras::from_raw(node.extras()),
+            };
+            let handle = ctx.add_labeled_asset(format!("Node{}", node.index()), node_asset);
+
+            if let Some(name) = node.name() {
+                named_nodes.insert(String::from(name), handle.clone());
+            }
+            nodes.push(handle);
+        }
+
+        /*
+         * 3.1) Process skins
+         */
+        let mut skins = Vec::with_capacity(document.skins().len());
+
+        for skin in document.skins() {
+            let inverse_bindposes = skin.load_inverse_bindposes(ctx).await?;
+            let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+            skins.push((inverse_bindposes, joints));
+        }
+
         /*
          * 4) Process animations
          */
 
         #[cfg(feature = "animation")]
-        let (animations, named_animations) = {
+        let (animations, named_animations, animated_nodes) = {
             let mut animations = Vec::with_capacity(document.animations().len());
             let mut named_animations = HashMap::new();
+            // The nodes targeted by each animation
+            let mut animated_nodes: Vec<HashSet<usize>> =
+                Vec::with_capacity(document.animations().len());
 
             for animation in document.animations() {
                 let clip = animation.load_animation_clip(ctx).await?;
@@ -278,19 +724,34 @@ where
                     named_animations.insert(String::from(name), handle.clone());
                 }
                 animations.push(handle);
+                animated_nodes.push(
+                    animation
+                        .channels()
+                        .map(|channel| channel.node().index())
+                        .collect(),
+                );
             }
 
-            Ok((animations, named_animations))
+            Ok((animations, named_animations, animated_nodes))
         }?;
+        #[cfg(feature = "animation")]
+        let mut animation_graphs = Vec::new();
 
         /*
-         * 4) Process Scenes
+         * 5) Process Scenes
          */
-        let nodes = Vec::with_capacity(document.nodes().len());
-        let named_nodes = HashMap::new();
         let mut scenes: Vec<Handle<BevyScene>> = Vec::with_capacity(document.scenes().len());
         let mut named_scenes = HashMap::new();
 
+        if options.reflect_components_key.is_some() && self.type_registry().is_none() {
+            document.diagnostics().report(Diagnostic::warning(
+                ObjectPath::default(),
+                DiagnosticCode::MissingTypeRegistry,
+                "reflect_components_key is set but the transformer provides no type registry, \
+                 reflected components will not be inserted",
+            ));
+        }
+
         // Cache entities as we traverse up the tree
         let mut entity_cache: HashMap<usize, Entity> =
             HashMap::with_capacity(document.nodes().len());
@@ -299,10 +760,23 @@ where
             let mut scene_world = World::new();
             // Reset the entity mapping cache to remove old root-nodes
             entity_cache.clear();
+            // Every spawned node entity, used to resolve skin joints
+            let mut node_entities: HashMap<usize, Entity> = HashMap::new();
+            // Primitive entities paired with the skin of their node
+            let mut skinned_entities: Vec<(Entity, Skin)> = Vec::new();
+            // Animated node entities paired with their root node
+            #[cfg(feature = "animation")]
+            let mut animated_entities: Vec<(Entity, Node)> = Vec::new();
 
+            let mut active_camera_found = false;
+            let scene_extras = gltf::GltfSceneExtras::from_raw(scene.extras());
+            let roots: Vec<usize> = scene.nodes().map(|n| n.index()).collect();
+
+            // The traversal is collected up-front so that it is not held across
+            // the `process_node` await points
             let filter = |s, n| self.node_filter(s, n);
-            let filtered_traversal =
-                FilteredDepthFirst::new(document, scene.nodes(), scene.clone(), &filter);
+            let filtered_traversal: Vec<Node> =
+                FilteredDepthFirst::new(document, scene.nodes(), scene.clone(), &filter).collect();
 
             for node in filtered_traversal {
                 // Create child component ahead of time to prevent archetype moves
@@ -313,18 +787,208 @@ where
                 let mut node_entity =
                     scene_world.spawn((child_component, node.transform(), Visibility::default()));
 
+                if let Some(name) = options.unnamed_nodes.name_for(&node) {
+                    node_entity.insert(name);
+                }
+
+                if options
+                    .cameras
+                    .insert(&node, &mut node_entity, !active_camera_found)
+                {
+                    active_camera_found = true;
+                }
+
+                #[cfg(feature = "bevy_3d")]
+                options.lights.spawn(&node, &mut node_entity);
+
+                // Insert reflected components authored in the node extras
+                if let (Some(key), Some(registry)) =
+                    (&options.reflect_components_key, self.type_registry())
+                {
+                    reflect::insert_reflect_components(
+                        document,
+                        &registry.read(),
+                        key,
+                        &node,
+                        ObjectPath::from(scene.path_segment()).join(node.path_segment()),
+                        &mut node_entity,
+                    );
+                }
+
+                // Attach extras at the same levels as `bevy_gltf`
+                if let Some(extras) = gltf::GltfExtras::from_raw(node.extras()) {
+                    node_entity.insert(extras);
+                }
+                if let Some(extras) = scene_extras
+                    .as_ref()
+                    .filter(|_| roots.contains(&node.index()))
+                {
+                    node_entity.insert(extras.clone());
+                }
+                if let Some(mesh) = node.mesh() {
+                    if let Some(extras) = gltf::GltfMeshExtras::from_raw(mesh.extras()) {
+                        node_entity.insert(extras);
+                    }
+                }
+
+                // Spawn an entity for each primitive of the mesh
+                if let Some(primitives) = node
+                    .mesh()
+                    .and_then(|mesh| scene_primitives.get(mesh.index()))
+                {
+                    let skin = node.skin();
+
+                    // Morph target weights are shared by all primitives, the
+                    // primitives without targets are not animated.
+                    let morph_weights = primitives
+                        .iter()
+                        .map(|primitive| primitive.morph_targets)
+                        .find(|&count| count != 0)
+                        .map(|count| match node.weights() {
+                            Some(weights) => weights.to_vec(),
+                            None => vec![0.0; count],
+                        });
+                    if let Some(weights) = &morph_weights {
+                        node_entity.insert(
+                            MorphWeights::new(weights.clone(), None)
+                                .map_err(|e| Error::from(e).context(node.path_segment()))?,
+                        );
+                    }
+
+                    node_entity.with_children(|parent| {
+                        for primitive in primitives {
+                            let mut entity = parent.spawn((
+                                Transform::default(),
+                                Visibility::default(),
+                                self.mesh_components(primitive.mesh.clone()),
+                            ));
+
+                            if let Some(material) = &primitive.material {
+                                entity.insert(self.material_components(material.clone()));
+                            }
+                            if let Some(extras) = &primitive.extras {
+                                entity.insert(extras.clone());
+                            }
+                            if let Some(extras) = &primitive.mat_extras {
+                                entity.insert(extras.clone());
+                            }
+                            if let Some(weights) = morph_weights
+                                .as_ref()
+                                .filter(|_| primitive.morph_targets != 0)
+                            {
+                                // Cannot fail, `MorphWeights::new` accepted the same weights
+                                entity.insert(MeshMorphWeights::new(weights.clone()).unwrap());
+                            }
+                            if let Some(skin) = &skin {
+                                skinned_entities.push((entity.id(), skin.clone()));
+                            }
+                        }
+                    });
+                }
+
                 // Attach children
                 for child in node.children() {
                     let Some(child_entity) = entity_cache.remove(&child.index()) else {
-                        warn!("Missing child entity");
+                        // Children pruned by the node filter are expected to be missing
+                        if !filter(scene.clone(), child.clone()) {
+                            continue;
+                        }
+
+                        document.diagnostics().report(Diagnostic::warning(
+                            ObjectPath::from(scene.path_segment())
+                                .join(node.path_segment())
+                                .join(child.path_segment()),
+                            DiagnosticCode::MissingChildEntity,
+                            "Missing child entity",
+                        ));
                         continue;
                     };
 
                     node_entity.add_child(child_entity);
                 }
 
+                if let Some(handlers) = self.extension_handlers() {
+                    handlers.process_node(&node, ctx, &mut node_entity)?;
+                }
+
+                self.process_node(ctx, settings, scene.clone(), node.clone(), &mut node_entity)
+                    .await?;
+
+                #[cfg(feature = "animation")]
+                if animated_nodes
+                    .iter()
+                    .any(|nodes| nodes.contains(&node.index()))
+                {
+                    animated_entities.push((node_entity.id(), node.clone()));
+                }
+
                 // Insert into the cache
                 entity_cache.insert(node.index(), node_entity.id());
+                node_entities.insert(node.index(), node_entity.id());
+            }
+
+            // Skins are resolved once all joint entities have been spawned
+            for (entity, skin) in skinned_entities {
+                let (inverse_bindposes, joint_indices) = &skins[skin.index()];
+                let Some(joints) = joint_indices
+                    .iter()
+                    .map(|joint| node_entities.get(joint).copied())
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    document.diagnostics().report(Diagnostic::warning(
+                        ObjectPath::from(scene.path_segment()).join(skin.path_segment()),
+                        DiagnosticCode::MissingJointEntity,
+                        "Missing joint entity, the mesh will not be skinned",
+                    ));
+                    continue;
+                };
+
+                scene_world.entity_mut(entity).insert(SkinnedMesh {
+                    inverse_bindposes: inverse_bindposes.clone(),
+                    joints,
+                });
+            }
+
+            // Animation players are placed on the roots of the animated trees
+            #[cfg(feature = "animation")]
+            {
+                let graph = options.animation_graphs.then(|| {
+                    let clips = animations
+                        .iter()
+                        .zip(&animated_nodes)
+                        .filter(|(_, nodes)| nodes.iter().any(|n| node_entities.contains_key(n)))
+                        .map(|(clip, _)| clip.clone());
+                    let (graph, _) = AnimationGraph::from_clips(clips);
+                    ctx.add_labeled_asset(format!("AnimationGraph{}", scene.index()), graph)
+                });
+
+                for (entity, node) in animated_entities {
+                    let Some(player) = node
+                        .root()
+                        .and_then(|root| node_entities.get(&root.index()).copied())
+                    else {
+                        continue;
+                    };
+
+                    scene_world.entity_mut(entity).insert(AnimationTarget {
+                        id: AnimationTargetId::from_names(node.path().iter()),
+                        player,
+                    });
+
+                    let mut player = scene_world.entity_mut(player);
+                    if !player.contains::<AnimationPlayer>() {
+                        player.insert(AnimationPlayer::default());
+                        if let Some(graph) = &graph {
+                            player.insert(AnimationGraphHandle(graph.clone()));
+                        }
+                    }
+                }
+
+                animation_graphs.extend(graph);
+            }
+
+            if let Some(handlers) = self.extension_handlers() {
+                handlers.process_scene(&scene, ctx, &mut scene_world)?;
             }
 
             let scene_asset = BevyScene::new(scene_world);
@@ -352,10 +1016,23 @@ where
             animations,
             #[cfg(feature = "animation")]
             named_animations,
+            #[cfg(feature = "animation")]
+            animation_graphs,
+            diagnostics: options
+                .attach_diagnostics
+                .then(|| document.diagnostics().to_vec()),
         })
     }
 
     fn extensions(&self) -> &[&str] {
         <S as SimpleGltfTransformer>::extensions(self)
     }
+
+    fn supported_gltf_extensions(&self) -> &[&str] {
+        <S as SimpleGltfTransformer>::supported_gltf_extensions(self)
+    }
+
+    fn extension_handlers(&self) -> Option<&ExtensionRegistry> {
+        <S as SimpleGltfTransformer>::extension_handlers(self)
+    }
 }
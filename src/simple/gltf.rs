@@ -1,26 +1,63 @@
 //! glTF Asset data that mirrors the defaults provided by `bevy_gltf`
 
-use crate::wrap::camera::Projection;
 #[cfg(feature = "bevy_3d")]
 use crate::wrap::light::LightKind;
+use crate::{diagnostics::Diagnostic, wrap::camera::Projection};
 #[cfg(feature = "animation")]
-use bevy::animation::AnimationClip;
+use bevy::animation::{graph::AnimationGraph, AnimationClip};
 use bevy::{
     asset::Asset,
+    ecs::reflect::ReflectComponent,
     prelude::{Component, Handle},
     reflect::{Reflect, TypePath},
     scene::Scene,
     transform::components::Transform,
     utils::HashMap,
 };
+use serde_json::value::RawValue;
 
-/// Application specific glTF extra data
-#[derive(Debug, Clone, Component, Reflect)]
-pub struct GltfExtras {
-    /// JSON encoded extra data
-    pub value: String,
+macro_rules! extras_component {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Component, Reflect)]
+        #[reflect(Component)]
+        pub struct $name {
+            /// JSON encoded extra data
+            pub value: String,
+        }
+
+        impl $name {
+            /// Copies the raw JSON extras of a glTF object if present
+            pub fn from_raw(extras: Option<&RawValue>) -> Option<Self> {
+                extras.map(|raw| Self {
+                    value: raw.get().to_string(),
+                })
+            }
+        }
+    };
 }
 
+extras_component!(
+    /// Application specific glTF extra data
+    GltfExtras
+);
+extras_component!(
+    /// Application specific extra data of the glTF scene a node belongs to
+    ///
+    /// This is attached to the root node entities of a scene.
+    GltfSceneExtras
+);
+extras_component!(
+    /// Application specific extra data of the glTF mesh at a node
+    GltfMeshExtras
+);
+extras_component!(
+    /// Application specific extra data of the glTF material used by a primitive
+    ///
+    /// This is attached to the primitive entities spawned for a node's mesh.
+    GltfMaterialExtras
+);
+
 /// Loaded glTF assets with custom Mesh and Material types
 #[derive(Asset, TypePath)]
 pub struct Gltf<Mesh, Mat>
@@ -52,6 +89,13 @@ where
     /// Named glTF animations
     #[cfg(feature = "animation")]
     pub named_animations: HashMap<String, Handle<AnimationClip>>,
+    /// Generated animation graphs for each scene, only present when requested
+    /// with [LoadOptions::animation_graphs](crate::simple::LoadOptions::animation_graphs)
+    #[cfg(feature = "animation")]
+    pub animation_graphs: Vec<Handle<AnimationGraph>>,
+    /// Diagnostics recorded while loading, only present when requested with
+    /// [LoadOptions::attach_diagnostics](crate::simple::LoadOptions::attach_diagnostics)
+    pub diagnostics: Option<Vec<Diagnostic>>,
 }
 
 /// A glTF mesh, which may consist of multiple [GltfPrimitive]s and an optional [GltfExtras].
@@ -97,7 +141,7 @@ where
     Mat: Asset,
 {
     /// Direct children of the node
-    pub children: Vec<GltfNode<Mesh, Mat>>,
+    pub children: Vec<Handle<GltfNode<Mesh, Mat>>>,
     /// The mesh at this node
     pub mesh: Option<Handle<GltfMesh<Mesh, Mat>>>,
     /// The camera at this node
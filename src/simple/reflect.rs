@@ -0,0 +1,88 @@
+//! Insertion of reflected components authored in node extras
+use crate::{
+    diagnostics::{Diagnostic, DiagnosticCode},
+    error::ObjectPath,
+    wrap::{Document, Node},
+};
+use bevy::{
+    ecs::{reflect::ReflectComponent, world::EntityWorldMut},
+    reflect::{serde::TypedReflectDeserializer, TypeRegistry},
+};
+use serde::de::DeserializeSeed;
+use serde_json::Value;
+
+/// Deserializes the components stored under `key` in the extras of `node` and
+/// inserts them on `entity`.
+///
+/// The extras entry is expected to be a JSON object mapping type paths to
+/// component values, matching the component format of `bevy_scene`:
+///
+/// ```json
+/// { "components": { "my_game::Health": { "max": 100.0 } } }
+/// ```
+///
+/// Short type paths are accepted when they are unambiguous. Entries which
+/// cannot be inserted are reported as diagnostics on the [Document].
+pub(crate) fn insert_reflect_components(
+    document: Document<'_>,
+    registry: &TypeRegistry,
+    key: &str,
+    node: &Node<'_>,
+    path: ObjectPath,
+    entity: &mut EntityWorldMut<'_>,
+) {
+    let report = |code, message: String| {
+        document
+            .diagnostics()
+            .report(Diagnostic::warning(path.clone(), code, message));
+    };
+
+    let Some(extras) = node.extras() else {
+        return;
+    };
+    let Ok(Value::Object(mut extras)) = serde_json::from_str::<Value>(extras.get()) else {
+        return;
+    };
+    let components = match extras.remove(key) {
+        Some(Value::Object(components)) => components,
+        Some(_) => {
+            report(
+                DiagnosticCode::InvalidReflectComponent,
+                format!("extras entry `{key}` must be an object of components"),
+            );
+            return;
+        }
+        None => return,
+    };
+
+    for (type_path, value) in components {
+        let Some(registration) = registry
+            .get_with_type_path(&type_path)
+            .or_else(|| registry.get_with_short_type_path(&type_path))
+        else {
+            let message = if registry.is_ambiguous(&type_path) {
+                format!("short type path `{type_path}` is ambiguous, use the full type path")
+            } else {
+                format!("type `{type_path}` is not registered")
+            };
+            report(DiagnosticCode::UnknownReflectType, message);
+            continue;
+        };
+
+        let Some(reflect_component) = registration.data::<ReflectComponent>() else {
+            report(
+                DiagnosticCode::InvalidReflectComponent,
+                format!("type `{type_path}` does not reflect `Component`"),
+            );
+            continue;
+        };
+
+        match TypedReflectDeserializer::new(registration, registry).deserialize(&value) {
+            Ok(component) => reflect_component.insert(entity, component.as_ref(), registry),
+            Err(e) => report(
+                DiagnosticCode::InvalidReflectComponent,
+                format!("failed to deserialize `{type_path}`: {e}"),
+            ),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Helpers for running unit tests against a loaded [Document]
+use crate::{error, wrap::Document, GltfTransformLoader, GltfTransformer};
+use base64::Engine;
+use bevy::{
+    asset::{
+        io::{
+            memory::{Dir, MemoryAssetReader},
+            AssetSource,
+        },
+        AssetApp, AssetPlugin, AssetServer, Handle, LoadContext, LoadState,
+    },
+    prelude::*,
+};
+use serde_json::Value;
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+};
+
+/// A test which runs against a [Document] inside of an asset loader
+pub(crate) trait DocumentTest: Send + Sync + 'static {
+    /// The result returned to the test
+    type Output: Send + 'static;
+
+    fn run(
+        &self,
+        doc: Document<'_>,
+        ctx: &mut LoadContext<'_>,
+    ) -> impl Future<Output = Self::Output> + Send;
+}
+
+#[derive(Asset, TypePath)]
+struct TestAsset;
+
+struct TestTransformer<T: DocumentTest> {
+    test: T,
+    output: Mutex<mpsc::Sender<T::Output>>,
+}
+
+impl<T: DocumentTest> GltfTransformer for TestTransformer<T> {
+    type Asset = TestAsset;
+    type Settings = ();
+    type Error = error::Error;
+
+    async fn load<'a>(
+        &'a self,
+        document: Document<'_>,
+        _settings: &'a Self::Settings,
+        ctx: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let output = self.test.run(document, ctx).await;
+        self.output.lock().unwrap().send(output).unwrap();
+        Ok(TestAsset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+}
+
+/// Loads `file` (a `.gltf` or `.glb` file) and runs `test` against it
+///
+/// Additional files which are referenced by `file` can be provided in `files`.
+pub(crate) fn run_document_test<T: DocumentTest>(
+    file: &str,
+    files: &[(&str, Vec<u8>)],
+    test: T,
+) -> T::Output {
+    let dir = Dir::new(PathBuf::new());
+    for (path, data) in files {
+        dir.insert_asset(Path::new(path), data.clone());
+    }
+
+    let (sender, receiver) = mpsc::channel();
+
+    let mut app = App::new();
+    app.register_asset_source(
+        "memory",
+        AssetSource::build().with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+    )
+    .add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+    .init_asset::<TestAsset>()
+    .init_asset::<bevy::image::Image>()
+    .register_asset_loader(GltfTransformLoader(TestTransformer {
+        test,
+        output: Mutex::new(sender),
+    }));
+
+    let handle: Handle<TestAsset> = app
+        .world()
+        .resource::<AssetServer>()
+        .load(format!("memory://{file}"));
+
+    for _ in 0..1000 {
+        app.update();
+
+        if let Ok(output) = receiver.try_recv() {
+            return output;
+        }
+        if let LoadState::Failed(e) = app.world().resource::<AssetServer>().load_state(&handle) {
+            panic!("failed to load {file}: {e}");
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    panic!("timed out loading {file}");
+}
+
+/// Builds a `.gltf` file from `json`, adding a buffer with the contents of
+/// `bin` as a data URI if the JSON does not define any buffers
+pub(crate) fn gltf_file(mut json: Value, bin: &[u8]) -> Vec<u8> {
+    json["asset"] = serde_json::json!({ "version": "2.0" });
+
+    if json.get("buffers").is_none() && !bin.is_empty() {
+        let data = base64::engine::general_purpose::STANDARD.encode(bin);
+        json["buffers"] = serde_json::json!([{
+            "byteLength": bin.len(),
+            "uri": format!("data:application/octet-stream;base64,{data}"),
+        }]);
+    }
+
+    serde_json::to_vec(&json).unwrap()
+}
@@ -52,15 +52,15 @@ pub(crate) struct OwningSlice {
 
 impl OwningSlice {
     pub(crate) fn find_offset(root: &[u8], slice: &[u8]) -> Option<isize> {
-        let root_ptr = root.as_ptr();
-        let slice_ptr = slice.as_ptr();
+        // Compare addresses rather than using `offset_from` as the slice is
+        // not guaranteed to be part of the same allocation.
+        let root_start = root.as_ptr() as usize;
+        let slice_start = slice.as_ptr() as usize;
 
-        unsafe {
-            let offset = slice_ptr.offset_from(root_ptr);
+        let offset = slice_start.checked_sub(root_start)?;
+        let end = offset.checked_add(slice.len())?;
 
-            (offset >= 0 && ((offset + slice.len() as isize) < root.len() as isize))
-                .then_some(offset)
-        }
+        (end <= root.len()).then_some(offset as isize)
     }
 
     pub(crate) fn new_complete(allocation: Box<[u8]>) -> Self {
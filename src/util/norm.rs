@@ -10,6 +10,18 @@ impl Normalizable for u8 {
 
 impl Normalizable for u16 {
     fn norm(&self) -> f32 {
-        *self as f32 / 65525.0
+        *self as f32 / 65535.0
+    }
+}
+
+impl Normalizable for i8 {
+    fn norm(&self) -> f32 {
+        (*self as f32 / 127.0).max(-1.0)
+    }
+}
+
+impl Normalizable for i16 {
+    fn norm(&self) -> f32 {
+        (*self as f32 / 32767.0).max(-1.0)
     }
 }
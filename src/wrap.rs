@@ -14,13 +14,22 @@ pub mod mesh;
 pub mod scene;
 pub mod skins;
 pub mod texture;
-
-use crate::util::Cache;
+pub mod validate;
+
+use crate::{
+    diagnostics::Diagnostics,
+    error::{Error, ObjectPath, Result},
+    extension::ExtensionCache,
+    util::Cache,
+};
+use serde::de::DeserializeOwned;
+use serde_json::value::RawValue;
 use std::sync::OnceLock;
+use texture::TextureCache;
 
 pub use accessor::{Accessor, ElementShape, ElementType, Indices, Values};
 pub use animation::Animation;
-use bevy::{core::Name, utils::HashMap};
+use bevy::{asset::LoadContext, core::Name, utils::HashMap};
 pub use buffer::{Buffer, View};
 pub use camera::Camera;
 #[cfg(feature = "gltf_lights")]
@@ -30,10 +39,28 @@ pub use mesh::{Mesh, Primitive};
 pub use scene::{Node, Scene};
 pub use skins::Skin;
 pub use texture::{Image, Sampler, Texture};
+pub use validate::ValidationReport;
 
 const URI_ERROR: &str = "URI Contained invalid percent encoding";
 const VALID_MIME_TYPES: &[&str] = &["application/octet-stream", "application/gltf-buffer"];
 
+/// The glTF extensions interpreted by the [Document] wrappers with the
+/// currently enabled crate features.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    #[cfg(feature = "gltf_lights")]
+    "KHR_lights_punctual",
+    #[cfg(feature = "gltf_pbr")]
+    "KHR_materials_transmission",
+    #[cfg(feature = "gltf_pbr")]
+    "KHR_materials_ior",
+    #[cfg(feature = "gltf_pbr")]
+    "KHR_materials_emissive_strength",
+    #[cfg(feature = "gltf_pbr")]
+    "KHR_materials_unlit",
+    #[cfg(feature = "gltf_pbr")]
+    "KHR_materials_volume",
+];
+
 /// Buffer ID for the [Document] cache
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum BufferId {
@@ -54,14 +81,46 @@ pub(crate) struct DocInner {
     pub(crate) doc: gltf::Document,
     pub(crate) cache: Cache,
     pub(crate) paths: OnceLock<HashMap<usize, (usize, Vec<Name>)>>,
+    pub(crate) diagnostics: Diagnostics,
+    pub(crate) extensions: ExtensionCache,
+    pub(crate) textures: TextureCache,
+    #[cfg(feature = "gltf_lights")]
+    pub(crate) light_extensions: light::LightExtensions,
 }
 
 impl<'a> Document<'a> {
+    /// Returns the [Diagnostics] recorded while loading this glTF asset.
+    pub fn diagnostics(&self) -> &'a Diagnostics {
+        &self.inner.diagnostics
+    }
+
+    /// Checks the glTF data against the rules of the glTF 2.0 specification
+    ///
+    /// This loads all referenced buffers and inspects accessor data, vertex
+    /// indices, skinning attributes, normals, tangents, animation inputs and
+    /// the node hierarchy. Use [ValidationReport::into_result] to turn any
+    /// errors into [Error::Validation](crate::error::Error::Validation).
+    pub async fn validate(&self, ctx: &mut LoadContext<'_>) -> ValidationReport {
+        validate::validate(*self, ctx).await
+    }
+
     /// Returns the optionally defined default [Scene] for this glTF asset.
     pub fn default_scene(&self) -> Option<Scene<'a>> {
         self.inner.doc.default_scene().map(|s| Scene::new(*self, s))
     }
 
+    /// Returns an [Iterator] over the names of the extensions used somewhere
+    /// in this glTF asset.
+    pub fn extensions_used(&self) -> impl Iterator<Item = &'a str> {
+        self.inner.doc.extensions_used()
+    }
+
+    /// Returns an [Iterator] over the names of the extensions required to load
+    /// and render this glTF asset.
+    pub fn extensions_required(&self) -> impl Iterator<Item = &'a str> {
+        self.inner.doc.extensions_required()
+    }
+
     /// Returns an [Iterator] that visits the buffers of the glTF asset.
     pub fn buffers(&self) -> iter::Buffers<'a> {
         iter::Buffers::new(*self, self.inner.doc.buffers())
@@ -143,46 +202,59 @@ impl<'a> Document<'a> {
     }
 
     /// Helper function to compute and cache all the node-paths in the glTF file
+    ///
+    /// Each entry holds the index of the root node of the tree containing the
+    /// node and the names from that root down to the node.
     pub(crate) fn node_paths(&self) -> &'a HashMap<usize, (usize, Vec<Name>)> {
         self.inner.paths.get_or_init(|| {
-            use scene::traversal::{DepthFirst, Traversal};
-
-            // Get all the roots in all scenes
-            let roots = self
+            // Start from the roots in all scenes
+            let mut stack: Vec<(gltf::Node, usize, Vec<Name>)> = self
                 .inner
                 .doc
                 .scenes()
                 .flat_map(|s| s.nodes())
-                .map(|n| Node::new(*self, n));
+                .map(|n| (n.clone(), n.index(), Vec::new()))
+                .collect();
 
             // Output map
             let mut paths = HashMap::with_capacity(self.inner.doc.nodes().len());
 
-            // Construct all the node paths in reverse order
-            for node in DepthFirst::new(*self, roots, ()) {
-                let name = Name::new(
+            // Construct the node paths from the roots down, skipping nodes
+            // that were already visited so cycles cannot loop forever
+            while let Some((node, root, mut path)) = stack.pop() {
+                if paths.contains_key(&node.index()) {
+                    continue;
+                }
+
+                path.push(Name::new(
                     node.name()
                         .map(String::from)
                         .unwrap_or_else(|| format!("Node{}", node.index())),
-                );
-
-                // Insert self into map
-                paths.insert(node.index(), (0, vec![name.clone()]));
+                ));
 
-                // Add our name to all children
                 for child in node.children() {
-                    paths.get_mut(&child.index()).unwrap().1.push(name.clone());
+                    stack.push((child, root, path.clone()));
                 }
-            }
 
-            // Reverse all the path ordering
-            paths.values_mut().for_each(|path| path.1.reverse());
+                paths.insert(node.index(), (root, path));
+            }
 
             paths
         })
     }
 }
 
+/// Deserializes the raw `extras` of a glTF object, recording the `path` of the
+/// object in any [Error::Extras].
+pub(crate) fn extras_as<T: DeserializeOwned>(
+    extras: Option<&RawValue>,
+    path: impl FnOnce() -> ObjectPath,
+) -> Result<Option<T>> {
+    extras
+        .map(|raw| serde_json::from_str(raw.get()).map_err(|e| Error::Extras(e).with_path(path())))
+        .transpose()
+}
+
 /// Iterators for items in the glTF [Document]
 pub mod iter {
     use super::Document;
@@ -234,12 +306,17 @@ pub mod iter {
 
     use super::Primitive;
 
-    /// An iterator over [Primitive]s in a [Mesh]
-    pub struct Primitives<'a>(Document<'a>, gltf::mesh::iter::Primitives<'a>);
+    /// An iterator over [Primitive]s in a [Mesh](super::Mesh)
+    pub struct Primitives<'a>(
+        Document<'a>,
+        gltf::Mesh<'a>,
+        gltf::mesh::iter::Primitives<'a>,
+    );
 
     impl<'a> Primitives<'a> {
-        pub(crate) fn new(doc: Document<'a>, sub: gltf::mesh::iter::Primitives<'a>) -> Self {
-            Self(doc, sub)
+        pub(crate) fn new(doc: Document<'a>, mesh: gltf::Mesh<'a>) -> Self {
+            let sub = mesh.primitives();
+            Self(doc, mesh, sub)
         }
     }
 
@@ -247,13 +324,15 @@ pub mod iter {
         type Item = Primitive<'a>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            self.1.next().map(|i| Primitive::new(self.0, i))
+            self.2
+                .next()
+                .map(|i| Primitive::new(self.0, self.1.clone(), i))
         }
     }
 
     impl ExactSizeIterator for Primitives<'_> {
         fn len(&self) -> usize {
-            self.1.len()
+            self.2.len()
         }
     }
 
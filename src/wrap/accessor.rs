@@ -1,13 +1,18 @@
 //! Structures for glTF accessors
 //!
 use super::{Document, View};
+#[cfg(feature = "gltf_validations")]
+use crate::diagnostics::{Diagnostic, DiagnosticCode, Severity};
 use crate::{
     data::{sparse::IndexData, Accessible, Data, DenseData, Meta, SparseData, Untyped},
-    error::Result,
+    error::{Error, PathSegment, Result},
+    extension::GltfExtension,
 };
 use bevy::asset::LoadContext;
 use gltf::accessor::sparse::IndexType;
+use serde::de::DeserializeOwned;
 use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
 /// An accessor to data in some [View]
 pub struct Accessor<'a> {
@@ -23,9 +28,28 @@ impl<'a> Accessor<'a> {
     /// Load the data for this accessor without a specified transformation to
     /// rust types.
     pub async fn load_untyped(&self, ctx: &mut LoadContext<'_>) -> Result<Data<'a, Untyped>> {
+        self.load_untyped_inner(ctx)
+            .await
+            .map_err(|e| e.context(self.path_segment()))
+    }
+
+    async fn load_untyped_inner(&self, ctx: &mut LoadContext<'_>) -> Result<Data<'a, Untyped>> {
+        let data = self.load_data(ctx).await?;
+
+        #[cfg(feature = "gltf_validations")]
+        self.check_finite(&data);
+
+        Ok(data)
+    }
+
+    pub(super) async fn load_data(&self, ctx: &mut LoadContext<'_>) -> Result<Data<'a, Untyped>> {
         if let Some(sparse) = self.sparse() {
             let base = if let Some(base) = self.view() {
-                let data = &base.load(ctx).await?[self.offset()..];
+                let data = base
+                    .load(ctx)
+                    .await?
+                    .get(self.offset()..)
+                    .ok_or(Error::AccessorOutOfBounds)?;
 
                 Some(DenseData::new(Meta::from_accessor(&self.raw), data))
             } else {
@@ -41,20 +65,75 @@ impl<'a> Accessor<'a> {
                 indices,
                 values,
             )))
-        } else {
-            let data = &self.view().unwrap().load(ctx).await?[self.offset()..];
+        } else if let Some(view) = self.view() {
+            let data = view
+                .load(ctx)
+                .await?
+                .get(self.offset()..)
+                .ok_or(Error::AccessorOutOfBounds)?;
 
             Ok(Data::Dense(DenseData::new(
                 Meta::from_accessor(&self.raw),
                 data,
             )))
+        } else {
+            // Without a buffer view all elements are zero
+            Ok(Data::Sparse(SparseData::without_replacements(
+                Meta::from_accessor(&self.raw),
+                None,
+            )))
+        }
+    }
+
+    /// Reports a [Diagnostic] if any `f32` component of the data is infinite or NaN
+    #[cfg(feature = "gltf_validations")]
+    fn check_finite(&self, data: &Data<'a, Untyped>) {
+        if self.data_type() != gltf::accessor::DataType::F32 {
+            return;
+        }
+
+        let non_finite = (0..self.len())
+            .filter_map(|i| data.get_raw(i))
+            .flat_map(|raw| raw.chunks_exact(4))
+            .any(|c| !f32::from_le_bytes(c.try_into().unwrap()).is_finite());
+
+        if non_finite {
+            self.doc.diagnostics().report(Diagnostic::new(
+                self.path_segment(),
+                Severity::Error,
+                DiagnosticCode::NonFiniteValue,
+                "accessor contains Infinite or NaN f32 value",
+            ));
         }
     }
 
     /// Load the data for this accessor with a transformation to the specified
     /// rust type `T`
     pub async fn load<T: Accessible>(&self, ctx: &mut LoadContext<'_>) -> Result<Data<'a, T>> {
-        self.load_untyped(ctx).await?.try_with_type()
+        async { self.load_untyped_inner(ctx).await?.try_with_type() }
+            .await
+            .map_err(|e| e.context(self.path_segment()))
+    }
+
+    /// Load the data like [Accessor::load] without reporting non-finite
+    /// values, for the validator which reports them itself
+    pub(super) async fn load_unchecked<T: Accessible>(
+        &self,
+        ctx: &mut LoadContext<'_>,
+    ) -> Result<Data<'a, T>> {
+        async { self.load_data(ctx).await?.try_with_type() }
+            .await
+            .map_err(|e| e.context(self.path_segment()))
+    }
+
+    /// The internal glTF index of this [Accessor]
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.raw.index()
+    }
+
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Accessor(self.index())
     }
 
     /// Returns true if this accessor uses sparse data
@@ -173,10 +252,28 @@ impl<'a> Accessor<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Accessor].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras] with the path to this [Accessor].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
 }
 
 /// The dimensions and type of data from an [Accessor]
@@ -363,7 +460,12 @@ impl<'a> Indices<'a> {
 
     /// Load the data for the sparse indices as the appropriate index type
     pub async fn load(&self, ctx: &mut LoadContext<'_>) -> Result<IndexData<'a>> {
-        let view = &self.view().load(ctx).await?[self.offset()..];
+        let view = self
+            .view()
+            .load(ctx)
+            .await?
+            .get(self.offset()..)
+            .ok_or(Error::AccessorOutOfBounds)?;
 
         let untyped = DenseData::<'a, Untyped>::new(Meta::from_sparse_index(&self.accessor), view);
 
@@ -401,7 +503,12 @@ impl<'a> Values<'a> {
     /// Load the data for the sparse values without a transformation to an
     /// rust type.
     pub async fn load_untyped(&self, ctx: &mut LoadContext<'_>) -> Result<DenseData<'a, Untyped>> {
-        let view = &self.view().load(ctx).await?[self.offset()..];
+        let view = self
+            .view()
+            .load(ctx)
+            .await?
+            .get(self.offset()..)
+            .ok_or(Error::AccessorOutOfBounds)?;
 
         Ok(DenseData::new(
             Meta::from_sparse_values(&self.accessor),
@@ -1,11 +1,19 @@
 //! Structures for glTF animation
 
-use super::{Accessor, Document, Node};
-use crate::error::{Error, Result};
+use super::{Accessor, Document, ElementShape, ElementType, Node};
+use crate::{
+    data::{Accessible, Element},
+    error::{Error, PathSegment, Result},
+    extension::GltfExtension,
+    util::norm::Normalizable,
+};
 use bevy::{
     animation::{
         animated_field,
-        gltf_curves::{CubicKeyframeCurve, CubicRotationCurve, SteppedKeyframeCurve},
+        gltf_curves::{
+            CubicKeyframeCurve, CubicRotationCurve, SteppedKeyframeCurve, WideCubicKeyframeCurve,
+            WideLinearKeyframeCurve, WideSteppedKeyframeCurve,
+        },
         prelude::*,
         AnimationClip, AnimationTargetId, VariableCurve,
     },
@@ -18,6 +26,9 @@ use bevy::{
 };
 use gltf::animation::{Interpolation, Property};
 use iter::{Channels, Samplers};
+use serde::de::DeserializeOwned;
+use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
 /// A glTF animation description
 pub struct Animation<'a> {
@@ -45,6 +56,7 @@ impl<'a> Animation<'a> {
     pub fn channels(&self) -> Channels<'a> {
         Channels {
             doc: self.doc,
+            index: 0,
             raw: self.raw.channels(),
         }
     }
@@ -57,6 +69,13 @@ impl<'a> Animation<'a> {
         }
     }
 
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Animation {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
     /// Loads this animation as a bevy [AnimationClip] potentially remapping
     /// the [Channel]s
     ///
@@ -96,6 +115,39 @@ impl<'a> Animation<'a> {
         })
         .await
     }
+
+    /// Check if this item has data for the named extension
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.raw.extension_value(name).is_some()
+    }
+
+    /// Get the raw JSON data for the named extension if present
+    pub fn extension_value(&self, name: &str) -> Option<&Value> {
+        self.raw.extension_value(name)
+    }
+
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Animation].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
+    /// Application specific extra information as raw JSON data.
+    pub fn extras(&self) -> Option<&RawValue> {
+        self.raw.extras().as_deref()
+    }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Animation].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
 }
 
 /// Animation sampler data, provides input (time) and output (property) data
@@ -128,10 +180,17 @@ impl<'a> Sampler<'a> {
 /// Targets a [Sampler] to a particular property of a [Node]
 pub struct Channel<'a> {
     doc: Document<'a>,
+    index: usize,
     raw: gltf::animation::Channel<'a>,
 }
 
 impl<'a> Channel<'a> {
+    /// The index of this channel in the parent [Animation]
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
     /// Returns the parent [Animation]
     pub fn animation(&self) -> Animation<'a> {
         Animation {
@@ -168,16 +227,22 @@ impl<'a> Channel<'a> {
     }
 
     /// Load a bevy [VariableCurve] from this animation channel
+    ///
+    /// Errors are reported with the [Animation], [Channel] and [Accessor]
+    /// that failed to load as their [ObjectPath](crate::error::ObjectPath).
     pub async fn load_variable_curve(&self, ctx: &mut LoadContext<'_>) -> Result<VariableCurve> {
+        self.load_variable_curve_inner(ctx).await.map_err(|e| {
+            e.context(PathSegment::Channel(self.index))
+                .context(self.animation().path_segment())
+        })
+    }
+
+    async fn load_variable_curve_inner(&self, ctx: &mut LoadContext<'_>) -> Result<VariableCurve> {
         let sampler = self.sampler();
 
-        // Check that the keyframes are valid
-        let keyframes = sampler.input();
-        if keyframes.is_sparse() {
-            bevy::log::warn!("Sparse accessor not supported for animation sampler input");
-            return Err(Error::UnsupportedAccessor);
-        }
-        let keyframes = keyframes.load::<f32>(ctx).await?;
+        // Check that the keyframes are valid, sparse and dense inputs are
+        // both read through `Data`
+        let keyframes = sampler.input().load::<f32>(ctx).await?;
         if keyframes.count() == 0 {
             bevy::log::warn!("Tried to load animation with no keyframe timestamps");
             return Err(Error::MissingKeyframeTimestamps);
@@ -228,13 +293,77 @@ impl<'a> Channel<'a> {
             Property::Translation => make_curve!(animated_field!(Transform::translation), Vec3),
             Property::Rotation => make_curve!(animated_field!(Transform::rotation), Quat, rot),
             Property::Scale => make_curve!(animated_field!(Transform::scale), Vec3),
-            _ => todo!("Morph target weights"),
+            Property::MorphTargetWeights => {
+                // Each keyframe holds the weights of every morph target
+                let weights: Vec<f32> = output.try_with_type::<MorphWeight>()?.iter().collect();
+                if keyframes.count() == 1 {
+                    VariableCurve::new(WeightsCurve(ConstantCurve::new(
+                        Interval::EVERYWHERE,
+                        weights,
+                    )))
+                } else {
+                    match sampler.interpolation() {
+                        Interpolation::Linear => VariableCurve::new(WeightsCurve(
+                            WideLinearKeyframeCurve::new(keyframes.iter(), weights)
+                                .map_err(|_| Error::InvalidAnimationCurve)?,
+                        )),
+                        Interpolation::CubicSpline => VariableCurve::new(WeightsCurve(
+                            WideCubicKeyframeCurve::new(keyframes.iter(), weights)
+                                .map_err(|_| Error::InvalidAnimationCurve)?,
+                        )),
+                        Interpolation::Step => VariableCurve::new(WeightsCurve(
+                            WideSteppedKeyframeCurve::new(keyframes.iter(), weights)
+                                .map_err(|_| Error::InvalidAnimationCurve)?,
+                        )),
+                    }
+                }
+            }
         };
 
         Ok(curve)
     }
 }
 
+/// Reads morph target weights from an animation sampler output
+///
+/// ## Conversions
+///
+/// * `data: f32 => data`
+/// * `data: u8 | i8 | u16 | i16 => norm(data)`
+struct MorphWeight;
+
+impl Accessible for MorphWeight {
+    type Item = f32;
+
+    fn zero(_shape: ElementShape) -> Self::Item {
+        0.0
+    }
+
+    fn validate_accessor(shape: ElementShape) -> bool {
+        matches!(
+            shape,
+            ElementShape::Scalar(
+                ElementType::F32
+                    | ElementType::U8
+                    | ElementType::I8
+                    | ElementType::U16
+                    | ElementType::I16
+            )
+        )
+    }
+
+    fn from_element(mut elem: Element) -> Self::Item {
+        match elem.shape {
+            ElementShape::Scalar(ElementType::F32) => elem.read_f32(),
+            ElementShape::Scalar(ElementType::U8) => elem.read_u8().norm(),
+            ElementShape::Scalar(ElementType::I8) => elem.read_i8().norm(),
+            ElementShape::Scalar(ElementType::U16) => elem.read_u16().norm(),
+            ElementShape::Scalar(ElementType::I16) => elem.read_i16().norm(),
+            _ => unreachable!(),
+        }
+    }
+}
+
 /// Information about the target [Node] and [Property] for an animation [Channel]
 pub struct Target<'a> {
     doc: Document<'a>,
@@ -283,6 +412,7 @@ pub mod iter {
     /// Iterator over [Channel]s in an animation
     pub struct Channels<'a> {
         pub(super) doc: Document<'a>,
+        pub(super) index: usize,
         pub(super) raw: gltf::animation::iter::Channels<'a>,
     }
 
@@ -290,7 +420,15 @@ pub mod iter {
         type Item = Channel<'a>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            self.raw.next().map(|raw| Channel { doc: self.doc, raw })
+            let raw = self.raw.next()?;
+            let index = self.index;
+            self.index += 1;
+
+            Some(Channel {
+                doc: self.doc,
+                index,
+                raw,
+            })
         }
     }
 }
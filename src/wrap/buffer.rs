@@ -4,11 +4,13 @@
 use super::{BufferId, Document};
 use crate::{
     error::{Error, Result},
+    extension::GltfExtension,
     util::data_uri::DataUri,
 };
 use bevy::asset::LoadContext;
 use gltf::buffer::Source;
 use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
 /// Meta-data for a buffer from a glTF file
 pub struct Buffer<'a> {
@@ -63,6 +65,12 @@ impl<'a> Buffer<'a> {
         }
     }
 
+    /// Get the internal glTF index of this [Buffer]
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.raw.index()
+    }
+
     /// The length of the buffer in bytes.
     #[inline(always)]
     pub fn length(&self) -> usize {
@@ -85,6 +93,16 @@ impl<'a> Buffer<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Buffer].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
@@ -102,6 +120,12 @@ impl<'a> View<'a> {
         Self { doc, raw }
     }
 
+    /// Get the internal glTF index of this [View]
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.raw.index()
+    }
+
     /// Returns the parent [Buffer]
     #[inline(always)]
     pub fn buffer(&self) -> Buffer<'a> {
@@ -130,9 +154,16 @@ impl<'a> View<'a> {
     ///
     /// If the parent buffer data is external to the glTF file it will be added
     /// as a load dependency of the provided [LoadContext]
+    ///
+    /// Returns [Error::ViewOutOfBounds] if the view starts beyond the end of
+    /// the loaded buffer data.
     #[inline(always)]
     pub async fn load(&self, ctx: &mut LoadContext<'_>) -> Result<&'a [u8]> {
-        Ok(&self.buffer().load(ctx).await?[self.offset()..])
+        self.buffer()
+            .load(ctx)
+            .await?
+            .get(self.offset()..)
+            .ok_or(Error::ViewOutOfBounds)
     }
 
     /// Check if this item has data for the named extension
@@ -145,6 +176,16 @@ impl<'a> View<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [View].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
@@ -1,6 +1,14 @@
 //! Structures for camera configuration
 
 use super::Document;
+use crate::{
+    error::{PathSegment, Result},
+    extension::GltfExtension,
+};
+use bevy::render::camera::ScalingMode;
+use serde::de::DeserializeOwned;
+use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
 /// Information about a Camera's projection
 pub struct Camera<'a> {
@@ -25,57 +33,120 @@ impl<'a> Camera<'a> {
         self.raw.name()
     }
 
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Camera {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
+    /// Returns the glTF projection with its raw parameters
+    #[inline(always)]
+    pub fn gltf_projection(&self) -> gltf::camera::Projection<'_> {
+        self.raw.projection()
+    }
+
     /// Returns the camera projection as a bevy projection component
     pub fn projection(&self) -> Projection {
-        match self.raw.projection() {
-            gltf::camera::Projection::Orthographic(ortho) => {
-                Projection::Orthographic(bevy::prelude::OrthographicProjection {
-                    near: ortho.znear(),
-                    far: ortho.zfar(),
-                    ..bevy::prelude::OrthographicProjection::default_3d()
-                })
-            }
-            gltf::camera::Projection::Perspective(persp) => {
-                Projection::Perspective(bevy::prelude::PerspectiveProjection {
-                    fov: persp.yfov(),
-                    aspect_ratio: persp.aspect_ratio().unwrap_or(1.0),
-                    near: persp.znear(),
-                    far: persp.zfar().unwrap_or(1000.0),
-                })
-            }
-        }
+        self.convert_projection(bevy::prelude::OrthographicProjection::default_3d())
     }
 
     /// Returns the camera projection as a bevy projection component, defaults
     /// orthographic cameras to the Bevy 2D default.
     pub fn projection_2d(&self) -> Projection {
+        self.convert_projection(bevy::prelude::OrthographicProjection::default_2d())
+    }
+
+    /// Converts the glTF projection, filling the fields glTF does not define
+    /// from `ortho_default`
+    ///
+    /// * Orthographic `xmag`/`ymag` are half extents and map onto
+    ///   [ScalingMode::Fixed] with twice their size.
+    /// * A perspective camera without an `aspectRatio` uses Bevy's default,
+    ///   which Bevy replaces with the viewport's aspect ratio when rendering.
+    /// * A perspective camera without a `zfar` is infinite. Bevy always uses
+    ///   an infinite projection matrix and only uses the far plane for culling,
+    ///   so this becomes [f32::MAX], as [f32::INFINITY] produces NaN frustum
+    ///   planes.
+    fn convert_projection(
+        &self,
+        ortho_default: bevy::prelude::OrthographicProjection,
+    ) -> Projection {
         match self.raw.projection() {
             gltf::camera::Projection::Orthographic(ortho) => {
                 Projection::Orthographic(bevy::prelude::OrthographicProjection {
                     near: ortho.znear(),
                     far: ortho.zfar(),
-                    ..bevy::prelude::OrthographicProjection::default_2d()
+                    scaling_mode: ScalingMode::Fixed {
+                        width: 2.0 * ortho.xmag(),
+                        height: 2.0 * ortho.ymag(),
+                    },
+                    ..ortho_default
                 })
             }
             gltf::camera::Projection::Perspective(persp) => {
+                let default = bevy::prelude::PerspectiveProjection::default();
                 Projection::Perspective(bevy::prelude::PerspectiveProjection {
                     fov: persp.yfov(),
-                    aspect_ratio: persp.aspect_ratio().unwrap_or(1.0),
+                    aspect_ratio: persp.aspect_ratio().unwrap_or(default.aspect_ratio),
                     near: persp.znear(),
-                    far: persp.zfar().unwrap_or(1000.0),
+                    far: persp.zfar().unwrap_or(f32::MAX),
                 })
             }
         }
     }
+
+    /// Check if this item has data for the named extension
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.raw.extension_value(name).is_some()
+    }
+
+    /// Get the raw JSON data for the named extension if present
+    pub fn extension_value(&self, name: &str) -> Option<&Value> {
+        self.raw.extension_value(name)
+    }
+
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Camera].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self._doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
+    /// Application specific extra information as raw JSON data.
+    pub fn extras(&self) -> Option<&RawValue> {
+        self.raw.extras().as_deref()
+    }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Camera].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
 }
 
 /// A Camera projection
 ///
 /// The glTF file does not specify if the camera is intended to be 2d or 3d but
 /// the type of projection typically implies its expected use.
+#[derive(Debug, Clone)]
 pub enum Projection {
     /// Orthographic "2D" projection
     Orthographic(bevy::prelude::OrthographicProjection),
     /// Perspective "3D" projection
     Perspective(bevy::prelude::PerspectiveProjection),
 }
+
+impl From<Projection> for bevy::render::camera::Projection {
+    fn from(value: Projection) -> Self {
+        match value {
+            Projection::Orthographic(ortho) => Self::Orthographic(ortho),
+            Projection::Perspective(persp) => Self::Perspective(persp),
+        }
+    }
+}
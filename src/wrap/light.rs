@@ -1,9 +1,64 @@
 //! Structures for glTF lights from the `KHR_lights_punctual` extension
 //!
 use super::Document;
+use crate::{
+    diagnostics::{Diagnostic, DiagnosticCode, Diagnostics},
+    error::{PathSegment, Result},
+    extension::GltfExtension,
+};
 use bevy::color::Color;
 use gltf::khr_lights_punctual::Kind;
-use serde_json::value::RawValue;
+use serde::de::DeserializeOwned;
+use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
+
+/// Parsed `extensions` objects of each light, indexed like the lights
+pub(crate) type LightExtensions = Vec<serde_json::Map<String, Value>>;
+
+/// Parses the extension data of every light in `document`
+///
+/// The [gltf] crate only keeps the extensions of lights as raw JSON, so they
+/// are parsed once up front for [Light::extension_value]. Lights with malformed
+/// extension data are reported to `diagnostics` and treated as having none.
+pub(crate) fn parse_extensions(
+    document: gltf::Document,
+    diagnostics: &Diagnostics,
+) -> (gltf::Document, LightExtensions) {
+    let json = document.into_json();
+    let extensions = json
+        .extensions
+        .as_ref()
+        .and_then(|ext| ext.khr_lights_punctual.as_ref())
+        .map(|ext| {
+            ext.lights
+                .iter()
+                .enumerate()
+                .map(|(index, light)| {
+                    let raw = light.extensions.as_ref()?;
+                    serde_json::from_str(raw.get())
+                        .inspect_err(|e| {
+                            diagnostics.report(Diagnostic::warning(
+                                PathSegment::Light {
+                                    index,
+                                    name: light.name.clone(),
+                                },
+                                DiagnosticCode::InvalidExtension,
+                                format!("failed to parse light extensions: {e}"),
+                            ));
+                        })
+                        .ok()
+                })
+                .map(Option::unwrap_or_default)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // The document was already validated when it was first parsed
+    (
+        gltf::Document::from_json_without_validation(json),
+        extensions,
+    )
+}
 
 /// A glTF light from the `KHR_lights_punctual` extension
 pub struct Light<'a> {
@@ -35,6 +90,22 @@ impl<'a> Light<'a> {
         self.raw.name()
     }
 
+    /// Looks up the extension data parsed by [parse_extensions]
+    fn raw_extension_value(&self, name: &str) -> Option<&'a Value> {
+        self._doc
+            .inner
+            .light_extensions
+            .get(self.index())?
+            .get(name)
+    }
+
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Light {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
     /// Intensity of the light source defined in glTF
     ///
     /// For [Kind::Point] and [Kind::Spot] lights this is luminosity
@@ -74,11 +145,39 @@ impl<'a> Light<'a> {
         self.raw.kind()
     }
 
+    /// Check if this item has data for the named extension
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.raw_extension_value(name).is_some()
+    }
+
+    /// Get the raw JSON data for the named extension if present
+    pub fn extension_value(&self, name: &str) -> Option<&Value> {
+        self.raw_extension_value(name)
+    }
+
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Light].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self._doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw_extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
 
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Light].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
+
     /// Converts this [Light] into its corresponding Bevy light type.
     ///
     /// This uses the same conversion as the default Bevy glTF crate.
@@ -95,7 +194,7 @@ impl<'a> Light<'a> {
             Kind::Point => LightKind::Point(PointLight {
                 color: self.color(),
                 intensity: self.intensity_bevy(),
-                range: self.range().unwrap_or(2.0),
+                range: self.range().unwrap_or(20.0),
                 radius: 0.0,
                 ..Default::default()
             }),
@@ -118,6 +217,7 @@ impl<'a> Light<'a> {
 
 /// One of Bevy's PBR light types
 #[cfg(feature = "bevy_3d")]
+#[derive(Debug, Clone)]
 pub enum LightKind {
     /// A directional "sun" light
     Directional(bevy::pbr::DirectionalLight),
@@ -1,8 +1,22 @@
 //! Structures for glTF material definitions
 use bevy::color::Color;
+#[cfg(feature = "bevy_3d")]
+use bevy::{
+    asset::LoadContext,
+    pbr::{StandardMaterial, UvChannel},
+    render::render_asset::RenderAssetUsages,
+};
+use serde::de::DeserializeOwned;
 use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
-use super::Document;
+use super::{Document, Texture};
+#[cfg(feature = "bevy_3d")]
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
+use crate::{
+    error::{PathSegment, Result},
+    extension::GltfExtension,
+};
 
 /// Information about a glTF material
 ///
@@ -29,6 +43,13 @@ impl<'a> Material<'a> {
         self.raw.name()
     }
 
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Material {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
     /// The optional alpha cutoff value of the material.
     #[inline(always)]
     pub fn alpha_cutoff(&self) -> Option<f32> {
@@ -106,6 +127,197 @@ impl<'a> Material<'a> {
         pbr.roughness_factor()
     }
 
+    /// Returns the normal map texture of the material
+    pub fn normal_texture(&self) -> Option<NormalTexture<'a>> {
+        self.raw.normal_texture().map(|raw| NormalTexture {
+            doc: self._doc,
+            raw,
+        })
+    }
+
+    /// Returns the ambient occlusion texture of the material
+    pub fn occlusion_texture(&self) -> Option<OcclusionTexture<'a>> {
+        self.raw.occlusion_texture().map(|raw| OcclusionTexture {
+            doc: self._doc,
+            raw,
+        })
+    }
+
+    /// Returns the emissive texture of the material
+    pub fn emissive_texture(&self) -> Option<TextureInfo<'a>> {
+        self.raw
+            .emissive_texture()
+            .map(|raw| TextureInfo::new(self._doc, raw))
+    }
+
+    /// Returns the linear RGB emissive factor of the material
+    ///
+    /// The default value is `[0.0, 0.0, 0.0]`.
+    #[inline(always)]
+    pub fn emissive_factor(&self) -> [f32; 3] {
+        self.raw.emissive_factor()
+    }
+
+    /// Converts this [Material] into a Bevy [StandardMaterial], loading all
+    /// of the textures it references with [Texture::load_labeled].
+    ///
+    /// Only the following fields are set:
+    ///  * Base color, metallic and roughness factors and textures
+    ///  * Normal map and occlusion textures
+    ///  * Emissive color and texture, including `KHR_materials_emissive_strength`
+    ///  * `KHR_materials_transmission` and `KHR_materials_ior` factors
+    ///  * `KHR_materials_volume` thickness factor and attenuation
+    ///  * `KHR_materials_unlit`
+    ///  * [AlphaMode](bevy::prelude::AlphaMode), with the alpha cutoff
+    ///    mapping to [AlphaMode::Mask](bevy::prelude::AlphaMode::Mask)
+    ///  * Double sided rendering and back-face culling, see
+    ///    [StandardMaterialSettings::scale_inverted]
+    ///  * The [UvChannel] of each texture
+    ///
+    /// Unlike the default Bevy glTF loader, `KHR_texture_transform`,
+    /// `KHR_materials_clearcoat` and `KHR_materials_anisotropy` are not
+    /// applied. Implement [SimpleGltfTransformer::process_material](crate::simple::SimpleGltfTransformer::process_material)
+    /// and read them with [Material::extension_value] if they are needed.
+    ///
+    /// [StandardMaterial] has no normal scale or occlusion strength and only
+    /// supports two UV channels. Values it cannot represent are reported as
+    /// diagnostics and ignored.
+    ///
+    /// Errors are reported with this [Material] and the [Texture] that failed
+    /// to load as their [ObjectPath](crate::error::ObjectPath).
+    #[cfg(feature = "bevy_3d")]
+    pub async fn as_standard_material(
+        &self,
+        ctx: &mut LoadContext<'_>,
+        settings: &StandardMaterialSettings,
+    ) -> Result<StandardMaterial> {
+        self.as_standard_material_inner(ctx, settings)
+            .await
+            .map_err(|e| e.context(self.path_segment()))
+    }
+
+    #[cfg(feature = "bevy_3d")]
+    async fn as_standard_material_inner(
+        &self,
+        ctx: &mut LoadContext<'_>,
+        settings: &StandardMaterialSettings,
+    ) -> Result<StandardMaterial> {
+        use bevy::{color::LinearRgba, prelude::AlphaMode, render::render_resource::Face};
+
+        let pbr = self.pbr_base();
+        let usage = settings.texture_usage;
+        let emissive = self.emissive_factor();
+
+        let mut material = StandardMaterial {
+            base_color: Color::linear_rgba(
+                pbr.base_color_value()[0],
+                pbr.base_color_value()[1],
+                pbr.base_color_value()[2],
+                pbr.base_color_value()[3],
+            ),
+            perceptual_roughness: self.perceptual_roughness(),
+            metallic: self.metallic(),
+            double_sided: self.double_sided(),
+            cull_mode: if self.double_sided() {
+                None
+            } else if settings.scale_inverted {
+                Some(Face::Front)
+            } else {
+                Some(Face::Back)
+            },
+            emissive: LinearRgba::rgb(emissive[0], emissive[1], emissive[2])
+                * self.raw.emissive_strength().unwrap_or(1.0),
+            specular_transmission: self
+                .raw
+                .transmission()
+                .map_or(0.0, |t| t.transmission_factor()),
+            ior: self.raw.ior().unwrap_or(1.5),
+            unlit: self.raw.unlit(),
+            alpha_mode: match self.alpha_mode() {
+                gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                gltf::material::AlphaMode::Mask => {
+                    AlphaMode::Mask(self.alpha_cutoff().unwrap_or(0.5))
+                }
+                gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+            },
+            ..Default::default()
+        };
+
+        if let Some(volume) = self.raw.volume() {
+            if volume.thickness_texture().is_some() {
+                self.report_unsupported("KHR_materials_volume.thicknessTexture is not supported");
+            }
+            let attenuation = volume.attenuation_color();
+            material.thickness = volume.thickness_factor();
+            material.attenuation_distance = volume.attenuation_distance();
+            material.attenuation_color =
+                Color::linear_rgb(attenuation[0], attenuation[1], attenuation[2]);
+        }
+
+        if let Some(info) = pbr.base_color_texture() {
+            material.base_color_channel = self.uv_channel("baseColorTexture", info.tex_coord());
+            material.base_color_texture =
+                Some(info.texture().load_labeled(ctx, true, usage).await?);
+        }
+
+        if let Some(info) = pbr.metallic_roughness_texture() {
+            material.metallic_roughness_channel =
+                self.uv_channel("metallicRoughnessTexture", info.tex_coord());
+            material.metallic_roughness_texture =
+                Some(info.texture().load_labeled(ctx, false, usage).await?);
+        }
+
+        if let Some(info) = self.normal_texture() {
+            if info.scale() != 1.0 {
+                self.report_unsupported("normalTexture.scale is not supported");
+            }
+            material.normal_map_channel = self.uv_channel("normalTexture", info.tex_coord());
+            material.normal_map_texture =
+                Some(info.texture().load_labeled(ctx, false, usage).await?);
+        }
+
+        if let Some(info) = self.occlusion_texture() {
+            if info.strength() != 1.0 {
+                self.report_unsupported("occlusionTexture.strength is not supported");
+            }
+            material.occlusion_channel = self.uv_channel("occlusionTexture", info.tex_coord());
+            material.occlusion_texture =
+                Some(info.texture().load_labeled(ctx, false, usage).await?);
+        }
+
+        if let Some(info) = self.emissive_texture() {
+            material.emissive_channel = self.uv_channel("emissiveTexture", info.tex_coord());
+            material.emissive_texture = Some(info.texture().load_labeled(ctx, true, usage).await?);
+        }
+
+        Ok(material)
+    }
+
+    /// Maps a glTF `texCoord` to a [UvChannel], falling back to
+    /// [UvChannel::Uv0] for unsupported channels
+    #[cfg(feature = "bevy_3d")]
+    fn uv_channel(&self, texture: &str, tex_coord: u32) -> UvChannel {
+        match tex_coord {
+            0 => UvChannel::Uv0,
+            1 => UvChannel::Uv1,
+            _ => {
+                self.report_unsupported(format!(
+                    "{texture} uses TEXCOORD_{tex_coord}, only 2 UV channels are supported"
+                ));
+                UvChannel::Uv0
+            }
+        }
+    }
+
+    #[cfg(feature = "bevy_3d")]
+    fn report_unsupported(&self, message: impl Into<String>) {
+        self._doc.diagnostics().report(Diagnostic::warning(
+            self.path_segment(),
+            DiagnosticCode::UnsupportedMaterialProperty,
+            message,
+        ));
+    }
+
     /// Check if this item has data for the named extension
     pub fn has_extension(&self, name: &str) -> bool {
         self.raw.extension_value(name).is_some()
@@ -116,10 +328,39 @@ impl<'a> Material<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Material].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self._doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Material].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
+}
+
+/// Settings for [Material::as_standard_material]
+#[cfg(feature = "bevy_3d")]
+#[derive(Debug, Clone, Default)]
+pub struct StandardMaterialSettings {
+    /// Expected usage of the loaded textures
+    pub texture_usage: RenderAssetUsages,
+    /// Cull front faces instead of back faces, for meshes whose node has a
+    /// negative scale
+    pub scale_inverted: bool,
 }
 
 /// Material information using the PBR Metallic-Roughness model
@@ -139,4 +380,156 @@ impl<'a> PBRMetallicRoughness<'a> {
     pub fn base_color_value(&self) -> [f32; 4] {
         self.raw.base_color_factor()
     }
+
+    /// Returns the base color texture, which stores sRGB encoded values
+    pub fn base_color_texture(&self) -> Option<TextureInfo<'a>> {
+        self.raw
+            .base_color_texture()
+            .map(|raw| TextureInfo::new(self._doc, raw))
+    }
+
+    /// Returns the metallic-roughness texture
+    ///
+    /// Metalness is sampled from the B channel and roughness from the G
+    /// channel, both stored as linear values.
+    pub fn metallic_roughness_texture(&self) -> Option<TextureInfo<'a>> {
+        self.raw
+            .metallic_roughness_texture()
+            .map(|raw| TextureInfo::new(self._doc, raw))
+    }
+}
+
+/// A reference to a [Texture] used by a [Material]
+pub struct TextureInfo<'a> {
+    doc: Document<'a>,
+    raw: gltf::texture::Info<'a>,
+}
+
+impl<'a> TextureInfo<'a> {
+    pub(crate) fn new(doc: Document<'a>, raw: gltf::texture::Info<'a>) -> Self {
+        Self { doc, raw }
+    }
+
+    /// Returns the referenced [Texture]
+    pub fn texture(&self) -> Texture<'a> {
+        Texture::new(self.doc, self.raw.texture())
+    }
+
+    /// The set index of the `TEXCOORD_n` attribute used to sample the texture
+    #[inline(always)]
+    pub fn tex_coord(&self) -> u32 {
+        self.raw.tex_coord()
+    }
+}
+
+/// A reference to the normal map [Texture] of a [Material]
+pub struct NormalTexture<'a> {
+    doc: Document<'a>,
+    raw: gltf::material::NormalTexture<'a>,
+}
+
+impl<'a> NormalTexture<'a> {
+    /// Returns the referenced [Texture]
+    pub fn texture(&self) -> Texture<'a> {
+        Texture::new(self.doc, self.raw.texture())
+    }
+
+    /// The set index of the `TEXCOORD_n` attribute used to sample the texture
+    #[inline(always)]
+    pub fn tex_coord(&self) -> u32 {
+        self.raw.tex_coord()
+    }
+
+    /// The scalar multiplier applied to each normal vector of the texture
+    ///
+    /// The default value is 1.0.
+    #[inline(always)]
+    pub fn scale(&self) -> f32 {
+        self.raw.scale()
+    }
+}
+
+/// A reference to the ambient occlusion [Texture] of a [Material]
+pub struct OcclusionTexture<'a> {
+    doc: Document<'a>,
+    raw: gltf::material::OcclusionTexture<'a>,
+}
+
+impl<'a> OcclusionTexture<'a> {
+    /// Returns the referenced [Texture]
+    pub fn texture(&self) -> Texture<'a> {
+        Texture::new(self.doc, self.raw.texture())
+    }
+
+    /// The set index of the `TEXCOORD_n` attribute used to sample the texture
+    #[inline(always)]
+    pub fn tex_coord(&self) -> u32 {
+        self.raw.tex_coord()
+    }
+
+    /// A scalar multiplier controlling the amount of occlusion applied
+    ///
+    /// The default value is 1.0.
+    #[inline(always)]
+    pub fn strength(&self) -> f32 {
+        self.raw.strength()
+    }
+}
+
+#[cfg(all(test, feature = "bevy_3d"))]
+mod tests {
+    use super::*;
+    use crate::test_util::{gltf_file, run_document_test, DocumentTest};
+    use serde_json::json;
+
+    /// Converts every material of the document with the default settings
+    struct Convert;
+
+    impl DocumentTest for Convert {
+        type Output = Vec<StandardMaterial>;
+
+        async fn run(&self, doc: Document<'_>, ctx: &mut LoadContext<'_>) -> Self::Output {
+            let mut materials = Vec::new();
+            for material in doc.materials() {
+                let settings = StandardMaterialSettings::default();
+                materials.push(material.as_standard_material(ctx, &settings).await.unwrap());
+            }
+            materials
+        }
+    }
+
+    #[test]
+    fn unlit_and_volume() {
+        let json = json!({
+            "extensionsUsed": ["KHR_materials_unlit", "KHR_materials_volume"],
+            "materials": [
+                {},
+                { "extensions": { "KHR_materials_unlit": {} } },
+                { "extensions": { "KHR_materials_volume": {
+                    "thicknessFactor": 0.5,
+                    "attenuationDistance": 2.0,
+                    "attenuationColor": [0.25, 0.5, 0.75],
+                } } },
+            ],
+        });
+
+        let materials =
+            run_document_test("test.gltf", &[("test.gltf", gltf_file(json, &[]))], Convert);
+
+        let [default, unlit, volume] = &materials[..] else {
+            panic!("expected 3 materials");
+        };
+
+        assert!(!default.unlit);
+        assert_eq!(default.thickness, 0.0);
+        assert_eq!(default.attenuation_distance, f32::INFINITY);
+        assert_eq!(default.attenuation_color, Color::WHITE);
+
+        assert!(unlit.unlit);
+
+        assert!(!volume.unlit);
+        assert_eq!(volume.thickness, 0.5);
+        assert_eq!(volume.attenuation_distance, 2.0);
+        assert_eq!(volume.attenuation_color, Color::linear_rgb(0.25, 0.5, 0.75));
+    }
 }
@@ -5,7 +5,9 @@ use self::iter::MorphTargets;
 use super::{iter::Primitives, Accessor, Document, ElementShape, ElementType, Material};
 use crate::{
     data::DataIter,
-    error::{Error, Result},
+    diagnostics::{Diagnostic, DiagnosticCode},
+    error::{Error, ObjectPath, PathSegment, Result},
+    extension::GltfExtension,
 };
 use bevy::{
     asset::LoadContext,
@@ -22,18 +24,21 @@ use bevy::{
 use bevy::{ecs::world::World, scene::Scene as BevyScene};
 
 use gltf::{mesh::Mode, Semantic};
+use serde::de::DeserializeOwned;
 use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
 /// A single primitive for a [Mesh] in a glTF file
 #[derive(Clone)]
 pub struct Primitive<'a> {
     doc: Document<'a>,
+    mesh: gltf::Mesh<'a>,
     raw: gltf::Primitive<'a>,
 }
 
 impl<'a> Primitive<'a> {
-    pub(crate) fn new(doc: Document<'a>, raw: gltf::Primitive<'a>) -> Self {
-        Self { doc, raw }
+    pub(crate) fn new(doc: Document<'a>, mesh: gltf::Mesh<'a>, raw: gltf::Primitive<'a>) -> Self {
+        Self { doc, mesh, raw }
     }
 
     /// Get the internal glTF index of this [Primitive]
@@ -42,6 +47,15 @@ impl<'a> Primitive<'a> {
         self.raw.index()
     }
 
+    /// Returns the parent [Mesh] of this [Primitive]
+    pub fn mesh(&self) -> Mesh<'a> {
+        Mesh::new(self.doc, self.mesh.clone())
+    }
+
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Primitive(self.index())
+    }
+
     /// Get the bounding box of the `POSITION` vertex attribute
     pub fn bounding_box(&self) -> Aabb3d {
         let gltf::mesh::BoundingBox { min, max } = self.raw.bounding_box();
@@ -76,6 +90,18 @@ impl<'a> Primitive<'a> {
         self.raw.get(semantic).map(|a| Accessor::new(self.doc, a))
     }
 
+    /// Returns an iterator over all of the vertex attributes and their accessors
+    pub fn attributes(&self) -> impl Iterator<Item = (Semantic, Accessor<'a>)> + '_ {
+        self.raw
+            .attributes()
+            .map(|(semantic, a)| (semantic, Accessor::new(self.doc, a)))
+    }
+
+    /// Get the accessor for the vertex indices if this primitive is indexed
+    pub fn indices(&self) -> Option<Accessor<'a>> {
+        self.raw.indices().map(|a| Accessor::new(self.doc, a))
+    }
+
     /// Returns an iterator over the all the [MorphTarget]s for this primitive
     pub fn morph_targets(&self) -> MorphTargets<'a> {
         iter::MorphTargets {
@@ -123,21 +149,45 @@ impl<'a> Primitive<'a> {
     /// normalization status. So for joint weights in the standard bevy loader
     /// a `[u8; 2]` normalized accessor would be accepted, but an unnormalized
     /// accessor would become an error.
+    ///
+    /// Errors are reported with the [Mesh], [Primitive], attribute and
+    /// [Accessor] that failed to load as their [ObjectPath](crate::error::ObjectPath).
     pub async fn as_mesh(
         &self,
         ctx: &mut LoadContext<'_>,
         asset_usage: RenderAssetUsages,
+    ) -> Result<BevyMesh> {
+        self.as_mesh_inner(ctx, asset_usage).await.map_err(|e| {
+            e.context(self.path_segment())
+                .context(self.mesh().path_segment())
+        })
+    }
+
+    async fn as_mesh_inner(
+        &self,
+        ctx: &mut LoadContext<'_>,
+        asset_usage: RenderAssetUsages,
     ) -> Result<BevyMesh> {
         let mut mesh = BevyMesh::new(self.topology()?, asset_usage);
 
         // Helper macro to filter out accessor type issues and skip those
         // attributes
         macro_rules! check_accessor {
-            ($accessor:ident.load::<$attr:ty>($ctx:ident)) => {
+            ($semantic:ident, $accessor:ident.load::<$attr:ty>($ctx:ident)) => {
                 match $accessor.load::<$attr>($ctx).await {
                     Ok(x) => x.iter().collect(),
-                    Err(Error::AccessorType { .. }) => continue,
-                    Err(e) => return Err(e),
+                    Err(e) if matches!(e.root_cause(), Error::AccessorType { .. }) => {
+                        self.doc.diagnostics().report(Diagnostic::warning(
+                            ObjectPath::from(self.mesh().path_segment())
+                                .join(self.path_segment())
+                                .join(PathSegment::Attribute($semantic.to_string()))
+                                .join($accessor.path_segment()),
+                            DiagnosticCode::SkippedAttribute,
+                            format!("attribute skipped: {}", e.root_cause()),
+                        ));
+                        continue;
+                    }
+                    Err(e) => return Err(e.context(PathSegment::Attribute($semantic.to_string()))),
                 }
             };
         }
@@ -145,59 +195,62 @@ impl<'a> Primitive<'a> {
         for (attr, raw_accessor) in self.raw.attributes() {
             let accessor = Accessor::new(self.doc, raw_accessor);
 
-            let (attr, value) =
-                match attr {
-                    Semantic::Positions => (
-                        BevyMesh::ATTRIBUTE_POSITION,
-                        VertexAttributeValues::Float32x3(check_accessor!(accessor
-                            .load::<attributes::AttrPosition>(
-                            ctx
-                        ))),
-                    ),
-                    Semantic::Normals => (
-                        BevyMesh::ATTRIBUTE_NORMAL,
-                        VertexAttributeValues::Float32x3(check_accessor!(
-                            accessor.load::<[f32; 3]>(ctx)
-                        )),
-                    ),
-                    Semantic::Tangents => (
-                        BevyMesh::ATTRIBUTE_TANGENT,
-                        VertexAttributeValues::Float32x3(check_accessor!(
-                            accessor.load::<[f32; 3]>(ctx)
-                        )),
-                    ),
-                    Semantic::TexCoords(c) if (0..=1).contains(&c) => (
-                        match c {
-                            0 => BevyMesh::ATTRIBUTE_UV_0,
-                            1 => BevyMesh::ATTRIBUTE_UV_1,
-                            _ => unreachable!(),
-                        },
-                        VertexAttributeValues::Float32x2(check_accessor!(accessor
-                            .load::<attributes::AttrTexCoord>(
-                            ctx
-                        ))),
-                    ),
-                    Semantic::Colors(0) => (
-                        BevyMesh::ATTRIBUTE_COLOR,
-                        VertexAttributeValues::Float32x4(check_accessor!(accessor
-                            .load::<attributes::AttrColor>(
-                            ctx
-                        ))),
-                    ),
-                    Semantic::Joints(0) => (
-                        BevyMesh::ATTRIBUTE_JOINT_INDEX,
-                        VertexAttributeValues::Uint16x4(check_accessor!(
-                            accessor.load::<attributes::AttrJointIndex>(ctx)
-                        )),
-                    ),
-                    Semantic::Weights(0) => (
-                        BevyMesh::ATTRIBUTE_JOINT_WEIGHT,
-                        VertexAttributeValues::Float32x4(check_accessor!(
-                            accessor.load::<attributes::AttrJointWeight>(ctx)
-                        )),
-                    ),
-                    _ => continue,
-                };
+            let (attr, value) = match attr {
+                Semantic::Positions => (
+                    BevyMesh::ATTRIBUTE_POSITION,
+                    VertexAttributeValues::Float32x3(check_accessor!(
+                        attr,
+                        accessor.load::<attributes::AttrPosition>(ctx)
+                    )),
+                ),
+                Semantic::Normals => (
+                    BevyMesh::ATTRIBUTE_NORMAL,
+                    VertexAttributeValues::Float32x3(check_accessor!(
+                        attr,
+                        accessor.load::<[f32; 3]>(ctx)
+                    )),
+                ),
+                Semantic::Tangents => (
+                    BevyMesh::ATTRIBUTE_TANGENT,
+                    VertexAttributeValues::Float32x3(check_accessor!(
+                        attr,
+                        accessor.load::<[f32; 3]>(ctx)
+                    )),
+                ),
+                Semantic::TexCoords(c) if (0..=1).contains(&c) => (
+                    match c {
+                        0 => BevyMesh::ATTRIBUTE_UV_0,
+                        1 => BevyMesh::ATTRIBUTE_UV_1,
+                        _ => unreachable!(),
+                    },
+                    VertexAttributeValues::Float32x2(check_accessor!(
+                        attr,
+                        accessor.load::<attributes::AttrTexCoord>(ctx)
+                    )),
+                ),
+                Semantic::Colors(0) => (
+                    BevyMesh::ATTRIBUTE_COLOR,
+                    VertexAttributeValues::Float32x4(check_accessor!(
+                        attr,
+                        accessor.load::<attributes::AttrColor>(ctx)
+                    )),
+                ),
+                Semantic::Joints(0) => (
+                    BevyMesh::ATTRIBUTE_JOINT_INDEX,
+                    VertexAttributeValues::Uint16x4(check_accessor!(
+                        attr,
+                        accessor.load::<attributes::AttrJointIndex>(ctx)
+                    )),
+                ),
+                Semantic::Weights(0) => (
+                    BevyMesh::ATTRIBUTE_JOINT_WEIGHT,
+                    VertexAttributeValues::Float32x4(check_accessor!(
+                        attr,
+                        accessor.load::<attributes::AttrJointWeight>(ctx)
+                    )),
+                ),
+                _ => continue,
+            };
 
             mesh.insert_attribute(attr, value);
         }
@@ -205,23 +258,34 @@ impl<'a> Primitive<'a> {
         if let Some(raw_index_accessor) = self.raw.indices() {
             let indices = Accessor::new(self.doc, raw_index_accessor);
 
-            let indices = match indices.shape() {
-                ElementShape::Scalar(ElementType::U8) => Indices::U16(
-                    indices
-                        .load::<u8>(ctx)
-                        .await?
-                        .iter()
-                        .map(|i| i as u16)
-                        .collect(),
-                ),
-                ElementShape::Scalar(ElementType::U16) => {
-                    Indices::U16(indices.load::<u16>(ctx).await?.iter().collect())
-                }
-                ElementShape::Scalar(ElementType::U32) => {
-                    Indices::U32(indices.load::<u32>(ctx).await?.iter().collect())
-                }
-                _ => todo!("Invalid index type"),
-            };
+            let indices = async {
+                Ok(match indices.shape() {
+                    ElementShape::Scalar(ElementType::U8) => Indices::U16(
+                        indices
+                            .load::<u8>(ctx)
+                            .await?
+                            .iter()
+                            .map(|i| i as u16)
+                            .collect(),
+                    ),
+                    ElementShape::Scalar(ElementType::U16) => {
+                        Indices::U16(indices.load::<u16>(ctx).await?.iter().collect())
+                    }
+                    ElementShape::Scalar(ElementType::U32) => {
+                        Indices::U32(indices.load::<u32>(ctx).await?.iter().collect())
+                    }
+                    _ => {
+                        return Err(Error::AccessorType {
+                            requested: "u8, u16 or u32 indices",
+                            dt: indices.data_type(),
+                            dim: indices.dimensions(),
+                        }
+                        .context(indices.path_segment()))
+                    }
+                })
+            }
+            .await
+            .map_err(|e: Error| e.context(PathSegment::Attribute(String::from("indices"))))?;
 
             mesh.insert_indices(indices);
         }
@@ -239,6 +303,41 @@ impl<'a> Primitive<'a> {
             .ok_or(Error::PrimitiveVertexCount)?
             .len())
     }
+
+    /// Check if this item has data for the named extension
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.raw.extension_value(name).is_some()
+    }
+
+    /// Get the raw JSON data for the named extension if present
+    pub fn extension_value(&self, name: &str) -> Option<&Value> {
+        self.raw.extension_value(name)
+    }
+
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Primitive].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
+    /// Application specific extra information as raw JSON data.
+    pub fn extras(&self) -> Option<&RawValue> {
+        self.raw.extras().as_deref()
+    }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Primitive].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || {
+            ObjectPath::from(self.mesh().path_segment()).join(self.path_segment())
+        })
+    }
 }
 
 /// A mesh in a glTF file
@@ -270,24 +369,40 @@ impl<'a> Mesh<'a> {
 
     /// Returns an [Iterator] over all of the [Primitives] of this [Mesh]
     pub fn primitives(&self) -> Primitives<'a> {
-        Primitives::new(self._doc, self.raw.primitives())
+        Primitives::new(self._doc, self.raw.clone())
+    }
+
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Mesh {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
     }
 
     /// Generates a [Scene](BevyScene) that loads all of the [Primitive]s as
     /// as [Entities](bevy::prelude::Entity).
     ///
-    /// All materials will be loaded as [StandardMaterial](bevy::pbr::StandardMaterial).
+    /// All materials will be loaded as [StandardMaterial](bevy::pbr::StandardMaterial)
+    /// using [Material::as_standard_material], each material is only loaded once.
     #[cfg(feature = "bevy_3d")]
     pub async fn as_bevy_scene(
         &self,
         ctx: &mut LoadContext<'_>,
         asset_usage: RenderAssetUsages,
     ) -> Result<BevyScene> {
+        use super::material::StandardMaterialSettings;
         use bevy::{
-            color::Color,
+            asset::Handle,
             pbr::{MeshMaterial3d, StandardMaterial},
             render::mesh::Mesh3d,
+            utils::HashMap,
+        };
+
+        let settings = StandardMaterialSettings {
+            texture_usage: asset_usage,
+            ..Default::default()
         };
+        let mut materials: HashMap<Option<usize>, Handle<StandardMaterial>> = HashMap::new();
 
         let mut batch = Vec::with_capacity(self.primitives().len());
         for prim in self.primitives() {
@@ -297,12 +412,20 @@ impl<'a> Mesh<'a> {
                 mesh,
             );
 
-            // FIXME: Should actually load the material
-            let material = StandardMaterial::from(Color::WHITE);
-            let material = ctx.add_labeled_asset(
-                format!("mesh/{}/material/{}", self.raw.index(), 0),
-                material,
-            );
+            let gltf_material = prim.material();
+            let material = match materials.get(&gltf_material.index()) {
+                Some(material) => material.clone(),
+                None => {
+                    let material = gltf_material.as_standard_material(ctx, &settings).await?;
+                    let label = match gltf_material.index() {
+                        Some(index) => format!("mesh/{}/material/{}", self.raw.index(), index),
+                        None => format!("mesh/{}/material/default", self.raw.index()),
+                    };
+                    let material = ctx.add_labeled_asset(label, material);
+                    materials.insert(gltf_material.index(), material.clone());
+                    material
+                }
+            };
 
             batch.push((Mesh3d(mesh), MeshMaterial3d(material)));
         }
@@ -323,12 +446,32 @@ impl<'a> Mesh<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Mesh].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self._doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
 
-    /// Optional morph target weights
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Mesh].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
+
+    /// Optional default morph target weights
+    ///
+    /// These may be overridden by the [Node](super::Node) using this mesh.
     pub fn weights(&self) -> Option<&'a [f32]> {
         self.raw.weights()
     }
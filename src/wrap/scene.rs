@@ -4,11 +4,17 @@
 pub mod traversal;
 
 use self::traversal::Traversal;
-use super::Document;
 #[cfg(feature = "gltf_lights")]
 use super::Light;
+use super::{Camera, Document, Mesh, Skin};
+use crate::{
+    error::{PathSegment, Result},
+    extension::GltfExtension,
+};
 use bevy::{core::Name, math::Mat4, transform::components::Transform};
+use serde::de::DeserializeOwned;
 use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
 /// A glTF scene which defines the root of one or more [Node] trees
 #[derive(Clone)]
@@ -34,6 +40,13 @@ impl<'a> Scene<'a> {
         self.raw.name()
     }
 
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Scene {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
     /// Returns an iterator over all of the root [Node]s in this [Scene]
     pub fn nodes(&self) -> RootNodes<'a> {
         RootNodes(self.doc, self.raw.nodes())
@@ -49,11 +62,29 @@ impl<'a> Scene<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Scene].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
 
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Scene].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
+
     /// Perform a traversal over the [Node]s of a scene.
     pub fn walk_nodes<T>(&self) -> T
     where
@@ -92,8 +123,21 @@ impl<'a> Node<'a> {
     }
 
     /// Returns the named path of this [Node]
+    ///
+    /// The path is empty for nodes which are not part of any [Scene].
     pub fn path(&self) -> &[Name] {
-        &self.doc.node_paths().get(&self.index()).unwrap().1
+        self.doc
+            .node_paths()
+            .get(&self.index())
+            .map_or(&[], |(_, path)| path.as_slice())
+    }
+
+    /// Returns the root [Node] of the scene tree containing this [Node]
+    ///
+    /// Returns [None] for nodes which are not part of any [Scene].
+    pub fn root(&self) -> Option<Node<'a>> {
+        let (root, _) = self.doc.node_paths().get(&self.index())?;
+        self.doc.get_node(*root)
     }
 
     /// The raw glTF index of this [Node]
@@ -102,6 +146,33 @@ impl<'a> Node<'a> {
         self.raw.index()
     }
 
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Node {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
+    /// Check if this item has data for the named extension
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.raw.extension_value(name).is_some()
+    }
+
+    /// Get the raw JSON data for the named extension if present
+    pub fn extension_value(&self, name: &str) -> Option<&Value> {
+        self.raw.extension_value(name)
+    }
+
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Node].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Returns the [Node]'s [Transform]
     #[inline]
     pub fn transform(&self) -> Transform {
@@ -115,6 +186,31 @@ impl<'a> Node<'a> {
         self.raw.light().map(|l| Light::new(self.doc, l))
     }
 
+    /// Returns the [Camera] at this [Node]
+    pub fn camera(&self) -> Option<Camera<'a>> {
+        self.raw.camera().map(|c| Camera::new(self.doc, c))
+    }
+
+    /// Returns the [Mesh] at this [Node]
+    pub fn mesh(&self) -> Option<Mesh<'a>> {
+        self.raw.mesh().map(|m| Mesh::new(self.doc, m))
+    }
+
+    /// Returns the [Skin] used by the [Mesh] at this [Node]
+    pub fn skin(&self) -> Option<Skin<'a>> {
+        self.raw.skin().map(|s| Skin::new(self.doc, s))
+    }
+
+    /// Returns the morph target weights of this [Node]
+    ///
+    /// Falls back to the default [Mesh::weights] when the node does not
+    /// override them.
+    pub fn weights(&self) -> Option<&'a [f32]> {
+        self.raw
+            .weights()
+            .or_else(|| self.raw.mesh().and_then(|m| m.weights()))
+    }
+
     /// Returns an iterator over the children of this [Node]
     pub fn children(&self) -> Children<'a> {
         Children(self.doc, self.raw.children())
@@ -140,6 +236,19 @@ impl<'a> Node<'a> {
     {
         T::new(self.doc, Some(self.clone()).into_iter(), settings)
     }
+
+    /// Application specific extra information as raw JSON data.
+    pub fn extras(&self) -> Option<&RawValue> {
+        self.raw.extras().as_deref()
+    }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Node].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
 }
 
 /// An iterator over root nodes in a [Scene]
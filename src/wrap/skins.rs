@@ -1,8 +1,21 @@
 //! Structures for defining skinned meshes
 //!
 use super::{Accessor, Document, Node};
+use crate::{
+    error::{PathSegment, Result},
+    extension::GltfExtension,
+};
+use bevy::{
+    asset::{Handle, LoadContext},
+    math::Mat4,
+    render::mesh::skinning::SkinnedMeshInverseBindposes,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{value::RawValue, Value};
+use std::sync::Arc;
 
 /// Joints and inverse bind matrices for skinned meshes
+#[derive(Clone)]
 pub struct Skin<'a> {
     doc: Document<'a>,
     raw: gltf::Skin<'a>,
@@ -23,6 +36,13 @@ impl<'a> Skin<'a> {
         self.raw.name()
     }
 
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Skin {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
     /// Returns the accessor for the inverse bind matrices
     ///
     /// The accessor is expected to contain 4x4 f32 matrices.
@@ -35,6 +55,34 @@ impl<'a> Skin<'a> {
             .map(|a| Accessor::new(self.doc, a))
     }
 
+    /// Loads the inverse bind matrices as a labeled
+    /// [SkinnedMeshInverseBindposes] asset, e.g. `InverseBindMatrices3`
+    ///
+    /// Without an [inverse_bind_matrices](Self::inverse_bind_matrices)
+    /// accessor an identity matrix is used for each joint.
+    ///
+    /// Errors are reported with the [Skin] and [Accessor] that failed to load
+    /// as their [ObjectPath](crate::error::ObjectPath).
+    pub async fn load_inverse_bindposes(
+        &self,
+        ctx: &mut LoadContext<'_>,
+    ) -> Result<Handle<SkinnedMeshInverseBindposes>> {
+        let matrices: Vec<Mat4> = match self.inverse_bind_matrices() {
+            Some(accessor) => accessor
+                .load::<Mat4>(ctx)
+                .await
+                .map_err(|e| e.context(self.path_segment()))?
+                .iter()
+                .collect(),
+            None => vec![Mat4::IDENTITY; self.joints().len()],
+        };
+
+        Ok(ctx.add_labeled_asset(
+            format!("InverseBindMatrices{}", self.index()),
+            SkinnedMeshInverseBindposes::from(matrices),
+        ))
+    }
+
     /// Returns the [Node] that is used as the root of the skeleton
     ///
     /// When [None] the joint transforms are relative to the scene root.
@@ -49,6 +97,39 @@ impl<'a> Skin<'a> {
             raw: self.raw.joints(),
         }
     }
+
+    /// Check if this item has data for the named extension
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.raw.extension_value(name).is_some()
+    }
+
+    /// Get the raw JSON data for the named extension if present
+    pub fn extension_value(&self, name: &str) -> Option<&Value> {
+        self.raw.extension_value(name)
+    }
+
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Skin].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
+    /// Application specific extra information as raw JSON data.
+    pub fn extras(&self) -> Option<&RawValue> {
+        self.raw.extras().as_deref()
+    }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Skin].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
 }
 
 /// An [Iterator] over [Node]s that are used as joints in a skinned mesh
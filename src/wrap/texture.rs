@@ -1,42 +1,67 @@
 //! Structures for glTF images and textures
 //!
-use std::{borrow::Cow, path::PathBuf};
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+};
 
 use super::{Document, View};
-use crate::{error::Result, util::data_uri::DataUri};
+use crate::{
+    error::{Error, PathSegment, Result},
+    extension::GltfExtension,
+    util::data_uri::DataUri,
+};
 use bevy::{
-    asset::{AssetPath, LoadContext},
+    asset::{AssetPath, Handle, LoadContext},
     image::{
         CompressedImageFormats, Image as BevyImage, ImageAddressMode, ImageFilterMode,
         ImageSampler, ImageSamplerDescriptor, ImageType,
     },
     render::{render_asset::RenderAssetUsages, render_resource::TextureFormat},
+    utils::HashMap,
 };
 use gltf::texture::{MagFilter, MinFilter};
+use serde::de::DeserializeOwned;
 use serde_json::{value::RawValue, Value};
 
 macro_rules! magic_check {
-    (($mime_type:ident, $buffer:ident) =>$($feature:literal, $magic:expr, $fmt:expr, $err:literal;)*) => {
+    (($mime_type:ident, $buffer:ident) =>$($feature:literal, $check:expr, $fmt:expr, $err:literal;)*) => {
         if let Some($mime_type) = $mime_type {
             ImageType::MimeType($mime_type)
         } $(
-            else if $buffer.starts_with($magic) {
+            else if ($check)(&$buffer[..]) {
                 #[cfg(feature = $feature)]
                 {
                     ImageType::Format($fmt)
                 }
                 #[cfg(not(feature = $feature))]
                 {
-                    panic!($err)
+                    return Err(Error::ImageFeature($err));
                 }
             }
         )*
         else {
-            panic!("Could not identify image type.")
+            return Err(Error::UnknownImageFormat);
         }
     };
 }
 
+/// Matches image data which begins with `magic`
+fn starts_with(magic: &'static [u8]) -> impl Fn(&[u8]) -> bool {
+    move |data| data.starts_with(magic)
+}
+
+/// Matches image data which ends with `magic`
+fn ends_with(magic: &'static [u8]) -> impl Fn(&[u8]) -> bool {
+    move |data| data.ends_with(magic)
+}
+
+/// Matches a RIFF container holding a WebP image
+fn is_webp(data: &[u8]) -> bool {
+    data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP")
+}
+
 /// A raw glTF image. This contains pixel data but no information on texture
 /// sampler settings
 pub struct Image<'a> {
@@ -90,12 +115,31 @@ impl<'a> Image<'a> {
         self.source().load_direct(ctx).await
     }
 
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Image {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
     /// Loads the image as a bevy texture ([Image](BevyImage))
     /// with the specified settings.
+    ///
+    /// Errors are reported with this [Image] as their [ObjectPath](crate::error::ObjectPath).
     pub async fn load(
         &self,
         ctx: &mut LoadContext<'_>,
         settings: ImageLoadSettings,
+    ) -> Result<BevyImage> {
+        self.load_inner(ctx, settings)
+            .await
+            .map_err(|e| e.context(self.path_segment()))
+    }
+
+    async fn load_inner(
+        &self,
+        ctx: &mut LoadContext<'_>,
+        settings: ImageLoadSettings,
     ) -> Result<BevyImage> {
         let source: Source<'a> = self.source();
 
@@ -105,7 +149,7 @@ impl<'a> Image<'a> {
 
                 BevyImage::from_buffer(
                     #[cfg(all(debug_assertions, feature = "dds"))]
-                    format!("Image({}, {:?})", self.index, settings),
+                    format!("Image({}, {:?})", self.index(), settings),
                     data,
                     ImageType::MimeType(mime_type),
                     CompressedImageFormats::all(),
@@ -115,15 +159,6 @@ impl<'a> Image<'a> {
                 )?
             }
             Source::UriEncoded { uri, mime_type } => {
-                // NOTE: Magic numbers are not guarded under features so that
-                // the proper error messages can be reported to the user.
-                const PNG_MAGIC: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
-                const JPEG_MAGIC: &[u8] = &[0xFF, 0xD8, 0xFF];
-                const QOI_MAGIC: &[u8] = b"qoif";
-                const EXR_MAGIC: &[u8] = &[0x76, 0x2F, 0x31, 0x01];
-                const GIF_MAGIC_A: &[u8] = b"GIF87a";
-                const GIF_MAGIC_B: &[u8] = b"GIF89a";
-
                 let uri = percent_encoding::percent_decode_str(uri)
                     .decode_utf8()
                     .expect(super::URI_ERROR);
@@ -134,35 +169,11 @@ impl<'a> Image<'a> {
                     _ => unreachable!(),
                 };
 
-                // Try to get the MIME Type
-                let image_type = magic_check!((mime_type, buffer_bytes) =>
-                    "png", PNG_MAGIC, bevy::image::ImageFormat::Png, "PNG loading requires the `png` feature.";
-                    "jpeg", JPEG_MAGIC, bevy::image::ImageFormat::Jpeg, "JPEG loading requires the `jpeg` feature.";
-                    "qoi", QOI_MAGIC, bevy::image::ImageFormat::Qoi, "QOI loading requires the `qoi` feature.";
-                    "exr", EXR_MAGIC, bevy::image::ImageFormat::OpenExr, "OpenEXR loading requires the `exr` feature.";
-                    "gif", GIF_MAGIC_A, bevy::image::ImageFormat::Gif, "Gif loading requires the `gif` feature.";
-                    "gif", GIF_MAGIC_B, bevy::image::ImageFormat::Gif, "Gif loading requires the `gif` feature.";
-                    "ff", b"farbfeld", bevy::image::ImageFormat::Farbfeld, "Farbfeld loading requires the `ff` feature.";
-                    // BMP file magic numbers
-                    "bmp", b"BM", bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
-                    "bmp", b"BA", bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
-                    "bmp", b"CI", bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
-                    "bmp", b"CP", bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
-                    "bmp", b"IC", bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
-                    "bmp", b"PT", bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
-                    // Several Netbpm types
-                    "pnm", b"P1", bevy::image::ImageFormat::Pnm, "PBM loading requires the `pnm` feature.";
-                    "pnm", b"P4", bevy::image::ImageFormat::Pnm, "PBM loading requires the `pnm` feature.";
-                    "pnm", b"P2", bevy::image::ImageFormat::Pnm, "PGM loading requires the `pnm` feature.";
-                    "pnm", b"P5", bevy::image::ImageFormat::Pnm, "PGM loading requires the `pnm` feature.";
-                    "pnm", b"P3", bevy::image::ImageFormat::Pnm, "PPM loading requires the `pnm` feature.";
-                    "pnm", b"P6", bevy::image::ImageFormat::Pnm, "PPM loading requires the `pnm` feature.";
-                    // TODO:   Basis, HDR, ICO, KTX2, TGA, TIFF, Webp
-                );
+                let image_type = image_type(mime_type, &buffer_bytes)?;
 
                 BevyImage::from_buffer(
                     #[cfg(all(debug_assertions, feature = "dds"))]
-                    format!("Image({}, {:?})", self.index, settings),
+                    format!("Image({}, {:?})", self.index(), settings),
                     &buffer_bytes,
                     image_type,
                     CompressedImageFormats::all(),
@@ -205,10 +216,91 @@ impl<'a> Image<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Image].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Image].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
+}
+
+/// Determines the [ImageType] of data URI image data, probing the first few
+/// bytes when no mime type is given
+fn image_type<'b>(mime_type: Option<&'b str>, buffer_bytes: &[u8]) -> Result<ImageType<'b>> {
+    // NOTE: Magic numbers are not guarded under features so that
+    // the proper error messages can be reported to the user.
+    const PNG_MAGIC: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+    const JPEG_MAGIC: &[u8] = &[0xFF, 0xD8, 0xFF];
+    const QOI_MAGIC: &[u8] = b"qoif";
+    const EXR_MAGIC: &[u8] = &[0x76, 0x2F, 0x31, 0x01];
+    const GIF_MAGIC_A: &[u8] = b"GIF87a";
+    const GIF_MAGIC_B: &[u8] = b"GIF89a";
+    const KTX2_MAGIC: &[u8] = &[
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ];
+    const BASIS_MAGIC: &[u8] = b"sB";
+    const DDS_MAGIC: &[u8] = b"DDS ";
+    const HDR_MAGIC_A: &[u8] = b"#?RADIANCE";
+    const HDR_MAGIC_B: &[u8] = b"#?RGBE";
+    const ICO_MAGIC: &[u8] = &[0x00, 0x00, 0x01, 0x00];
+    const TIFF_MAGIC_LE: &[u8] = b"II*\0";
+    const TIFF_MAGIC_BE: &[u8] = b"MM\0*";
+    const TGA_FOOTER: &[u8] = b"TRUEVISION-XFILE.\0";
+
+    let image_type = magic_check!((mime_type, buffer_bytes) =>
+        "png", starts_with(PNG_MAGIC), bevy::image::ImageFormat::Png, "PNG loading requires the `png` feature.";
+        "jpeg", starts_with(JPEG_MAGIC), bevy::image::ImageFormat::Jpeg, "JPEG loading requires the `jpeg` feature.";
+        "qoi", starts_with(QOI_MAGIC), bevy::image::ImageFormat::Qoi, "QOI loading requires the `qoi` feature.";
+        "exr", starts_with(EXR_MAGIC), bevy::image::ImageFormat::OpenExr, "OpenEXR loading requires the `exr` feature.";
+        "gif", starts_with(GIF_MAGIC_A), bevy::image::ImageFormat::Gif, "Gif loading requires the `gif` feature.";
+        "gif", starts_with(GIF_MAGIC_B), bevy::image::ImageFormat::Gif, "Gif loading requires the `gif` feature.";
+        "ff", starts_with(b"farbfeld"), bevy::image::ImageFormat::Farbfeld, "Farbfeld loading requires the `ff` feature.";
+        "ktx2", starts_with(KTX2_MAGIC), bevy::image::ImageFormat::Ktx2, "KTX2 loading requires the `ktx2` feature.";
+        "dds", starts_with(DDS_MAGIC), bevy::image::ImageFormat::Dds, "DDS loading requires the `dds` feature.";
+        "hdr", starts_with(HDR_MAGIC_A), bevy::image::ImageFormat::Hdr, "HDR loading requires the `hdr` feature.";
+        "hdr", starts_with(HDR_MAGIC_B), bevy::image::ImageFormat::Hdr, "HDR loading requires the `hdr` feature.";
+        "tiff", starts_with(TIFF_MAGIC_LE), bevy::image::ImageFormat::Tiff, "TIFF loading requires the `tiff` feature.";
+        "tiff", starts_with(TIFF_MAGIC_BE), bevy::image::ImageFormat::Tiff, "TIFF loading requires the `tiff` feature.";
+        "webp", is_webp, bevy::image::ImageFormat::WebP, "WebP loading requires the `webp` feature.";
+        // TGA has no header magic, only version 2 files are identified by their
+        // footer. It is checked before the short magic numbers below which
+        // could also match the start of a TGA header.
+        "tga", ends_with(TGA_FOOTER), bevy::image::ImageFormat::Tga, "TGA loading requires the `tga` feature.";
+        "basis-universal", starts_with(BASIS_MAGIC), bevy::image::ImageFormat::Basis, "Basis loading requires the `basis-universal` feature.";
+        "ico", starts_with(ICO_MAGIC), bevy::image::ImageFormat::Ico, "ICO loading requires the `ico` feature.";
+        // BMP file magic numbers
+        "bmp", starts_with(b"BM"), bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
+        "bmp", starts_with(b"BA"), bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
+        "bmp", starts_with(b"CI"), bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
+        "bmp", starts_with(b"CP"), bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
+        "bmp", starts_with(b"IC"), bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
+        "bmp", starts_with(b"PT"), bevy::image::ImageFormat::Bmp, "Bmp loading requires the `bmp` feature";
+        // Several Netbpm types
+        "pnm", starts_with(b"P1"), bevy::image::ImageFormat::Pnm, "PBM loading requires the `pnm` feature.";
+        "pnm", starts_with(b"P4"), bevy::image::ImageFormat::Pnm, "PBM loading requires the `pnm` feature.";
+        "pnm", starts_with(b"P2"), bevy::image::ImageFormat::Pnm, "PGM loading requires the `pnm` feature.";
+        "pnm", starts_with(b"P5"), bevy::image::ImageFormat::Pnm, "PGM loading requires the `pnm` feature.";
+        "pnm", starts_with(b"P3"), bevy::image::ImageFormat::Pnm, "PPM loading requires the `pnm` feature.";
+        "pnm", starts_with(b"P6"), bevy::image::ImageFormat::Pnm, "PPM loading requires the `pnm` feature.";
+    );
+
+    Ok(image_type)
 }
 
 fn transform_format(fmt: TextureFormat, is_srgb: bool) -> TextureFormat {
@@ -236,7 +328,7 @@ fn transform_format(fmt: TextureFormat, is_srgb: bool) -> TextureFormat {
 }
 
 /// Minimal settings for loading an [Image] as a [Bevy texture](bevy::render::texture::Image)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ImageLoadSettings {
     /// Should the data be treated as sRGB
     pub is_srgb: bool,
@@ -324,6 +416,25 @@ impl<'a> Texture<'a> {
         Self { doc, raw }
     }
 
+    /// Returns the internal glTF index
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.raw.index()
+    }
+
+    /// Returns the optional user-defined name
+    #[inline(always)]
+    pub fn name(&self) -> Option<&str> {
+        self.raw.name()
+    }
+
+    pub(crate) fn path_segment(&self) -> PathSegment {
+        PathSegment::Texture {
+            index: self.index(),
+            name: self.name().map(String::from),
+        }
+    }
+
     /// The underlying [Image] that provides the texel data
     pub fn source(&self) -> Image<'a> {
         Image::new(self.doc, self.raw.source())
@@ -353,6 +464,92 @@ impl<'a> Texture<'a> {
             .await
     }
 
+    /// Load the [Texture] with its own [Sampler] as a labeled sub-asset
+    ///
+    /// Every texture is labeled the same way whatever the kind of its
+    /// [Source], e.g. `Texture3` or `Texture3#srgb` when loaded as sRGB, so
+    /// it can be addressed by label. See [Texture::load_cached] for how the
+    /// labels are assigned.
+    #[inline(always)]
+    pub async fn load_labeled(
+        &self,
+        ctx: &mut LoadContext<'_>,
+        is_srgb: bool,
+        asset_usage: RenderAssetUsages,
+    ) -> Result<Handle<BevyImage>> {
+        self.load_cached(
+            ctx,
+            ImageLoadSettings {
+                is_srgb,
+                sampler: self.sampler().as_bevy_sampler(),
+                asset_usage,
+            },
+        )
+        .await
+    }
+
+    /// Load the [Texture] as a labeled sub-asset, reusing the [Handle] of any
+    /// previous load of this [Texture] with the same settings
+    ///
+    /// The cache is shared by every load from the same [Document], so a
+    /// texture used by many materials is only decoded once for each
+    /// combination of sRGB flag, sampler and asset usage.
+    ///
+    /// Loads with this texture's own [Sampler] and the default
+    /// [RenderAssetUsages] are labeled `Texture{index}`, with a `#srgb` suffix
+    /// if loaded as sRGB. Any other combination gets an additional `#{n}`
+    /// suffix, counting from 1 for each texture and sRGB flag in the order the
+    /// combinations are first loaded, e.g. `Texture3#srgb#1`.
+    ///
+    /// The cache entry is reserved before the image is decoded, so a load
+    /// which starts while another load with the same settings is in progress
+    /// returns the same [Handle] without decoding the image again.
+    ///
+    /// Errors are reported with this [Texture] and its [Image] as their
+    /// [ObjectPath](crate::error::ObjectPath).
+    pub async fn load_cached(
+        &self,
+        ctx: &mut LoadContext<'_>,
+        settings: ImageLoadSettings,
+    ) -> Result<Handle<BevyImage>> {
+        let key = TextureKey::new(self.index(), &settings);
+        let own_sampler = TextureKey::sampler_key(&self.sampler().as_bevy_sampler());
+        let is_variant =
+            key.sampler != own_sampler || key.asset_usage != RenderAssetUsages::default();
+
+        let (label, handle) = {
+            let mut cache = self.doc.inner.textures.lock();
+            if let Some(handle) = cache.handles.get(&key) {
+                return Ok(handle.clone());
+            }
+
+            let mut label = format!("Texture{}", self.index());
+            if settings.is_srgb {
+                label.push_str("#srgb");
+            }
+            if is_variant {
+                let variant = cache.next_variant(self.index(), key.is_srgb);
+                label = format!("{label}#{variant}");
+            }
+
+            // Reserve the entry so concurrent loads share this handle
+            let handle = ctx.get_label_handle(label.clone());
+            cache.handles.insert(key.clone(), handle.clone());
+            (label, handle)
+        };
+
+        let image = match self.source().load(ctx, settings).await {
+            Ok(image) => image,
+            Err(e) => {
+                self.doc.inner.textures.lock().handles.remove(&key);
+                return Err(e.context(self.path_segment()));
+            }
+        };
+        ctx.add_labeled_asset(label, image);
+
+        Ok(handle)
+    }
+
     /// Check if this item has data for the named extension
     pub fn has_extension(&self, name: &str) -> bool {
         self.raw.extension_value(name).is_some()
@@ -363,10 +560,85 @@ impl<'a> Texture<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Texture].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self.doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
+
+    /// Deserializes the application specific [extras](Self::extras) into `T`
+    ///
+    /// Returns [None] if no extras are present. Failures are returned as
+    /// [Error::Extras](crate::error::Error::Extras) with the path to this [Texture].
+    pub fn extras_as<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        super::extras_as(self.extras(), || self.path_segment().into())
+    }
+}
+
+/// Identifies a texture loaded with particular [ImageLoadSettings]
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TextureKey {
+    index: usize,
+    is_srgb: bool,
+    // `ImageSampler` implements neither `Eq` nor `Hash` so samplers are
+    // compared by their serialized form
+    sampler: String,
+    asset_usage: RenderAssetUsages,
+}
+
+impl TextureKey {
+    fn new(index: usize, settings: &ImageLoadSettings) -> Self {
+        Self {
+            index,
+            is_srgb: settings.is_srgb,
+            sampler: Self::sampler_key(&settings.sampler),
+            asset_usage: settings.asset_usage,
+        }
+    }
+
+    fn sampler_key(sampler: &ImageSampler) -> String {
+        serde_json::to_string(sampler).expect("ImageSampler serialization should not fail")
+    }
+}
+
+/// Handles of the textures loaded from a [Document]
+#[derive(Default)]
+pub(crate) struct TextureCache {
+    inner: Mutex<TextureCacheInner>,
+}
+
+#[derive(Default)]
+struct TextureCacheInner {
+    /// Handles of loaded or loading textures
+    handles: HashMap<TextureKey, Handle<BevyImage>>,
+    /// The number of variant labels assigned for each texture index and sRGB
+    /// flag
+    variants: HashMap<(usize, bool), usize>,
+}
+
+impl TextureCache {
+    fn lock(&self) -> MutexGuard<'_, TextureCacheInner> {
+        self.inner.lock().unwrap()
+    }
+}
+
+impl TextureCacheInner {
+    /// Returns the next variant number, starting at 1, for the texture at
+    /// `index` with the sRGB flag `is_srgb`
+    fn next_variant(&mut self, index: usize, is_srgb: bool) -> usize {
+        let count = self.variants.entry((index, is_srgb)).or_default();
+        *count += 1;
+        *count
+    }
 }
 
 /// glTF texture sampling information
@@ -419,8 +691,399 @@ impl<'a> Sampler<'a> {
         self.raw.extension_value(name)
     }
 
+    /// Get the typed data for the [GltfExtension] `T` if present
+    ///
+    /// The data is deserialized once and cached for this [Sampler].
+    pub fn extension<T: GltfExtension>(&self) -> Result<Option<Arc<T>>> {
+        self._doc
+            .inner
+            .extensions
+            .get_or_parse(self.raw.extension_value(T::NAME))
+    }
+
     /// Application specific extra information as raw JSON data.
     pub fn extras(&self) -> Option<&RawValue> {
         self.raw.extras().as_deref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{gltf_file, run_document_test, DocumentTest};
+    use base64::Engine;
+    use serde_json::json;
+
+    /// Includes an image from the `test_data/images` corpus
+    macro_rules! test_image {
+        ($file:literal) => {
+            include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/test_data/images/",
+                $file
+            ))
+        };
+    }
+
+    /// An image from the corpus and how it is expected to load
+    struct Sample {
+        file: &'static str,
+        data: &'static [u8],
+        /// [None] if Bevy does not know a MIME type for the format
+        mime_type: Option<&'static str>,
+        /// The [Debug] name of the [ImageFormat](bevy::image::ImageFormat)
+        format: &'static str,
+        /// Whether the feature required by the format is enabled
+        enabled: bool,
+        /// The [Error::ImageFeature] message when the feature is disabled
+        feature_error: &'static str,
+        /// False if the sample only contains a header and cannot be decoded
+        complete: bool,
+    }
+
+    macro_rules! sample {
+        ($file:literal, $mime_type:expr, $feature:literal, $format:literal, $error:literal, $complete:literal) => {
+            Sample {
+                file: $file,
+                data: test_image!($file),
+                mime_type: $mime_type,
+                format: $format,
+                enabled: cfg!(feature = $feature),
+                feature_error: $error,
+                complete: $complete,
+            }
+        };
+    }
+
+    #[rustfmt::skip]
+    const SAMPLES: &[Sample] = &[
+        sample!("1x1.png", Some("image/png"), "png", "Png", "PNG loading requires the `png` feature.", true),
+        sample!("1x1.jpg", Some("image/jpeg"), "jpeg", "Jpeg", "JPEG loading requires the `jpeg` feature.", true),
+        sample!("1x1.qoi", Some("image/qoi"), "qoi", "Qoi", "QOI loading requires the `qoi` feature.", true),
+        sample!("1x1.pbm", Some("image/x-portable-bitmap"), "pnm", "Pnm", "PBM loading requires the `pnm` feature.", true),
+        sample!("1x1.bmp", Some("image/bmp"), "bmp", "Bmp", "Bmp loading requires the `bmp` feature", true),
+        sample!("1x1.tga", Some("image/x-tga"), "tga", "Tga", "TGA loading requires the `tga` feature.", true),
+        sample!("1x1.ff", None, "ff", "Farbfeld", "Farbfeld loading requires the `ff` feature.", true),
+        sample!("1x1.hdr", Some("image/vnd.radiance"), "hdr", "Hdr", "HDR loading requires the `hdr` feature.", true),
+        sample!("1x1.ico", Some("image/x-icon"), "ico", "Ico", "ICO loading requires the `ico` feature.", true),
+        sample!("1x1.tiff", Some("image/tiff"), "tiff", "Tiff", "TIFF loading requires the `tiff` feature.", true),
+        sample!("1x1.gif", Some("image/gif"), "gif", "Gif", "Gif loading requires the `gif` feature.", true),
+        sample!("1x1.webp", Some("image/webp"), "webp", "WebP", "WebP loading requires the `webp` feature.", true),
+        sample!("1x1.dds", Some("image/vnd-ms.dds"), "dds", "Dds", "DDS loading requires the `dds` feature.", true),
+        sample!("header.exr", Some("image/x-exr"), "exr", "OpenExr", "OpenEXR loading requires the `exr` feature.", false),
+        sample!("header.ktx2", Some("image/ktx2"), "ktx2", "Ktx2", "KTX2 loading requires the `ktx2` feature.", false),
+        sample!("header.basis", Some("image/basis"), "basis-universal", "Basis", "Basis loading requires the `basis-universal` feature.", false),
+    ];
+
+    const PNG: &[u8] = test_image!("1x1.png");
+
+    fn data_uri(mime_type: &str, data: &[u8]) -> String {
+        let data = base64::engine::general_purpose::STANDARD.encode(data);
+        format!("data:{mime_type};base64,{data}")
+    }
+
+    /// Loads the first texture with each of the settings and returns the label
+    /// of every handle, a sampler of [None] uses the texture's own sampler
+    struct LoadCached(Vec<(bool, Option<ImageSampler>, RenderAssetUsages)>);
+
+    impl DocumentTest for LoadCached {
+        type Output = Vec<String>;
+
+        async fn run(&self, doc: Document<'_>, ctx: &mut LoadContext<'_>) -> Self::Output {
+            let texture = doc.textures().next().unwrap();
+            let mut labels = Vec::new();
+
+            for (is_srgb, sampler, asset_usage) in self.0.iter().cloned() {
+                let settings = ImageLoadSettings {
+                    is_srgb,
+                    sampler: sampler.unwrap_or_else(|| texture.sampler().as_bevy_sampler()),
+                    asset_usage,
+                };
+                let handle = texture.load_cached(ctx, settings).await.unwrap();
+                labels.push(handle.path().unwrap().label().unwrap().to_string());
+            }
+
+            labels
+        }
+    }
+
+    #[test]
+    fn load_cached_labels() {
+        let json = json!({
+            "images": [{ "uri": data_uri("image/png", PNG) }],
+            "textures": [{ "source": 0 }],
+        });
+
+        let own = None;
+        let nearest = Some(ImageSampler::nearest());
+        let main_world = RenderAssetUsages::MAIN_WORLD;
+        let default = RenderAssetUsages::default();
+
+        let labels = run_document_test(
+            "test.gltf",
+            &[("test.gltf", gltf_file(json, &[]))],
+            LoadCached(vec![
+                (false, nearest.clone(), default),
+                (false, own.clone(), default),
+                (true, nearest.clone(), default),
+                (false, own.clone(), main_world),
+                (false, nearest.clone(), default),
+                (true, own.clone(), default),
+                (false, own, default),
+            ]),
+        );
+
+        assert_eq!(
+            labels,
+            [
+                "Texture0#1",
+                "Texture0",
+                "Texture0#srgb#1",
+                "Texture0#2",
+                "Texture0#1",
+                "Texture0#srgb",
+                "Texture0",
+            ]
+        );
+    }
+
+    /// Returns the [Debug] name of the identified format, the MIME type if it
+    /// was used, or the error message
+    fn identify(mime_type: Option<&str>, data: &[u8]) -> std::result::Result<String, String> {
+        match image_type(mime_type, data) {
+            Ok(ImageType::Format(format)) => Ok(format!("{format:?}")),
+            Ok(ImageType::MimeType(mime_type)) => Ok(mime_type.to_string()),
+            Ok(ImageType::Extension(_)) => unreachable!(),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn expected(
+        format: &str,
+        enabled: bool,
+        feature_error: &str,
+    ) -> std::result::Result<String, String> {
+        match enabled {
+            true => Ok(format.to_string()),
+            false => Err(feature_error.to_string()),
+        }
+    }
+
+    #[test]
+    fn image_type_samples() {
+        for sample in SAMPLES {
+            assert_eq!(
+                identify(None, sample.data),
+                expected(sample.format, sample.enabled, sample.feature_error),
+                "{}",
+                sample.file
+            );
+        }
+    }
+
+    #[test]
+    fn image_type_magic() {
+        macro_rules! magic {
+            ($magic:expr, $feature:literal, $format:literal, $error:literal) => {{
+                let magic: &[u8] = $magic;
+                (magic, cfg!(feature = $feature), $format, $error)
+            }};
+        }
+
+        #[rustfmt::skip]
+        let magic = [
+            magic!(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A], "png", "Png", "PNG loading requires the `png` feature."),
+            magic!(&[0xFF, 0xD8, 0xFF], "jpeg", "Jpeg", "JPEG loading requires the `jpeg` feature."),
+            magic!(b"qoif", "qoi", "Qoi", "QOI loading requires the `qoi` feature."),
+            magic!(&[0x76, 0x2F, 0x31, 0x01], "exr", "OpenExr", "OpenEXR loading requires the `exr` feature."),
+            magic!(b"GIF87a", "gif", "Gif", "Gif loading requires the `gif` feature."),
+            magic!(b"GIF89a", "gif", "Gif", "Gif loading requires the `gif` feature."),
+            magic!(b"farbfeld", "ff", "Farbfeld", "Farbfeld loading requires the `ff` feature."),
+            magic!(&[0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A], "ktx2", "Ktx2", "KTX2 loading requires the `ktx2` feature."),
+            magic!(b"DDS ", "dds", "Dds", "DDS loading requires the `dds` feature."),
+            magic!(b"#?RADIANCE", "hdr", "Hdr", "HDR loading requires the `hdr` feature."),
+            magic!(b"#?RGBE", "hdr", "Hdr", "HDR loading requires the `hdr` feature."),
+            magic!(b"II*\0", "tiff", "Tiff", "TIFF loading requires the `tiff` feature."),
+            magic!(b"MM\0*", "tiff", "Tiff", "TIFF loading requires the `tiff` feature."),
+            magic!(b"RIFF\0\0\0\0WEBP", "webp", "WebP", "WebP loading requires the `webp` feature."),
+            magic!(b"sB", "basis-universal", "Basis", "Basis loading requires the `basis-universal` feature."),
+            magic!(&[0x00, 0x00, 0x01, 0x00], "ico", "Ico", "ICO loading requires the `ico` feature."),
+            magic!(b"BM", "bmp", "Bmp", "Bmp loading requires the `bmp` feature"),
+            magic!(b"BA", "bmp", "Bmp", "Bmp loading requires the `bmp` feature"),
+            magic!(b"CI", "bmp", "Bmp", "Bmp loading requires the `bmp` feature"),
+            magic!(b"CP", "bmp", "Bmp", "Bmp loading requires the `bmp` feature"),
+            magic!(b"IC", "bmp", "Bmp", "Bmp loading requires the `bmp` feature"),
+            magic!(b"PT", "bmp", "Bmp", "Bmp loading requires the `bmp` feature"),
+            magic!(b"P1", "pnm", "Pnm", "PBM loading requires the `pnm` feature."),
+            magic!(b"P4", "pnm", "Pnm", "PBM loading requires the `pnm` feature."),
+            magic!(b"P2", "pnm", "Pnm", "PGM loading requires the `pnm` feature."),
+            magic!(b"P5", "pnm", "Pnm", "PGM loading requires the `pnm` feature."),
+            magic!(b"P3", "pnm", "Pnm", "PPM loading requires the `pnm` feature."),
+            magic!(b"P6", "pnm", "Pnm", "PPM loading requires the `pnm` feature."),
+        ];
+
+        for (magic, enabled, format, error) in magic {
+            let mut data = magic.to_vec();
+            data.extend([0; 32]);
+
+            assert_eq!(
+                identify(None, &data),
+                expected(format, enabled, error),
+                "{magic:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn image_type_webp_requires_riff_form() {
+        let mut wave = b"RIFF\0\0\0\0WAVE".to_vec();
+        wave.extend([0; 32]);
+        assert_eq!(
+            identify(None, &wave),
+            Err(Error::UnknownImageFormat.to_string())
+        );
+        assert_eq!(
+            identify(None, b"RIFF"),
+            Err(Error::UnknownImageFormat.to_string())
+        );
+    }
+
+    #[test]
+    fn image_type_tga_footer_before_short_magic() {
+        let footer = b"TRUEVISION-XFILE.\0";
+        let tga = expected(
+            "Tga",
+            cfg!(feature = "tga"),
+            "TGA loading requires the `tga` feature.",
+        );
+
+        // TGA headers can start with bytes matching the short magic numbers
+        let prefixes: [&[u8]; 5] = [b"sB", b"BM", b"P1", b"IC", &[0x00, 0x00, 0x01, 0x00]];
+        for prefix in prefixes {
+            let mut data = prefix.to_vec();
+            data.extend([0; 32]);
+            data.extend(footer);
+
+            assert_eq!(identify(None, &data), tga, "{prefix:?}");
+        }
+    }
+
+    #[test]
+    fn image_type_prefers_mime_type() {
+        assert_eq!(
+            identify(Some("image/png"), test_image!("1x1.tga")),
+            Ok(String::from("image/png"))
+        );
+    }
+
+    #[test]
+    fn image_type_unknown() {
+        for data in [&[0x01, 0x02, 0x03, 0x04][..], &[]] {
+            assert!(matches!(
+                image_type(None, data),
+                Err(Error::UnknownImageFormat)
+            ));
+        }
+    }
+
+    #[cfg(not(feature = "tga"))]
+    #[test]
+    fn image_feature_message() {
+        let error = image_type(None, test_image!("1x1.tga")).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::ImageFeature("TGA loading requires the `tga` feature.")
+        ));
+        assert_eq!(error.to_string(), "TGA loading requires the `tga` feature.");
+    }
+
+    /// Loads every image of the document, returning its size or the error
+    struct LoadImages;
+
+    impl DocumentTest for LoadImages {
+        type Output = Vec<std::result::Result<(u32, u32), String>>;
+
+        async fn run(&self, doc: Document<'_>, ctx: &mut LoadContext<'_>) -> Self::Output {
+            let mut results = Vec::new();
+
+            for image in doc.images() {
+                let settings = ImageLoadSettings {
+                    is_srgb: false,
+                    sampler: ImageSampler::Default,
+                    asset_usage: RenderAssetUsages::default(),
+                };
+                let loaded = image.load(ctx, settings).await;
+                results.push(
+                    loaded
+                        .map(|image| (image.width(), image.height()))
+                        .map_err(|e| e.to_string()),
+                );
+            }
+
+            results
+        }
+    }
+
+    /// Loads every sample from a buffer view with its MIME type and from a
+    /// data URI without a MIME type, which identifies it by [image_type]
+    #[test]
+    fn load_samples() {
+        let mut bin = Vec::new();
+        let mut views = Vec::new();
+        let mut images = Vec::new();
+
+        for (index, sample) in SAMPLES.iter().enumerate() {
+            views.push(json!({
+                "buffer": 0,
+                "byteOffset": bin.len(),
+                "byteLength": sample.data.len(),
+            }));
+            bin.extend(sample.data);
+
+            images.push(json!({
+                "bufferView": index,
+                "mimeType": sample.mime_type.unwrap_or("image/x-unknown"),
+            }));
+            images.push(json!({ "uri": data_uri("application/octet-stream", sample.data) }));
+        }
+
+        let json = json!({ "bufferViews": views, "images": images });
+        let results = run_document_test(
+            "test.gltf",
+            &[("test.gltf", gltf_file(json, &bin))],
+            LoadImages,
+        );
+
+        for (sample, loaded) in SAMPLES.iter().zip(results.chunks(2)) {
+            let [view, uri] = loaded else { unreachable!() };
+
+            match (sample.enabled, sample.complete) {
+                (true, true) => {
+                    assert_eq!(uri, &Ok((1, 1)), "{} from a data URI", sample.file);
+                    if sample.mime_type.is_some() {
+                        assert_eq!(view, &Ok((1, 1)), "{} from a view", sample.file);
+                    } else {
+                        assert!(view.is_err(), "{} from a view", sample.file);
+                    }
+                }
+                // Identified, but the header alone cannot be decoded
+                (true, false) => {
+                    for result in [view, uri] {
+                        let error = result.as_ref().unwrap_err();
+                        assert!(!error.contains(sample.feature_error), "{}", sample.file);
+                    }
+                }
+                (false, _) => {
+                    assert!(view.is_err(), "{} from a view", sample.file);
+                    let error = uri.as_ref().unwrap_err();
+                    assert!(
+                        error.contains(sample.feature_error),
+                        "{} from a data URI: {error}",
+                        sample.file
+                    );
+                }
+            }
+        }
+    }
+}
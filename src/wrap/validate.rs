@@ -0,0 +1,1115 @@
+//! Validation of a glTF [Document] against the core glTF 2.0 specification
+//!
+//! The [gltf] crate only validates the structure of the JSON data. The checks
+//! in this module also inspect the binary data referenced by the document, in
+//! the spirit of the Khronos glTF-Validator.
+use super::{
+    mesh::attributes::{AttrJointIndex, AttrJointWeight},
+    Accessor, Document, View,
+};
+use crate::{
+    data::{Data, Untyped},
+    diagnostics::{Diagnostic, DiagnosticCode, Severity},
+    error::{Error, ObjectPath, PathSegment, Result},
+};
+use bevy::asset::LoadContext;
+use gltf::{
+    accessor::{DataType, Dimensions},
+    Semantic,
+};
+
+/// Tolerance used when checking that normal and tangent vectors are unit length
+const UNIT_LENGTH_TOLERANCE: f32 = 0.005;
+/// Tolerance used when checking that joint weights sum to one
+const WEIGHT_SUM_TOLERANCE: f32 = 0.01;
+
+/// The result of [Document::validate]
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// All of the problems found in the [Document]
+    pub issues: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Returns true if no issue with [Severity::Error] was found
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns an iterator over all issues with [Severity::Error]
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.issues.iter().filter(|d| d.severity == Severity::Error)
+    }
+
+    /// Returns an iterator over all issues with [Severity::Warning]
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.issues
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+    }
+
+    /// Converts this report into [Error::Validation] if any errors were found
+    pub fn into_result(self) -> Result<Self> {
+        if self.is_valid() {
+            Ok(self)
+        } else {
+            Err(Error::Validation(self))
+        }
+    }
+
+    fn error(&mut self, path: impl Into<ObjectPath>, code: DiagnosticCode, message: String) {
+        self.issues
+            .push(Diagnostic::new(path, Severity::Error, code, message));
+    }
+}
+
+pub(super) async fn validate(doc: Document<'_>, ctx: &mut LoadContext<'_>) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    check_buffers(doc, ctx, &mut report).await;
+    check_views(doc, &mut report);
+    check_node_hierarchy(doc, &mut report);
+
+    for accessor in doc.accessors() {
+        check_accessor(&accessor, ctx, &mut report).await;
+    }
+
+    check_meshes(doc, ctx, &mut report).await;
+
+    #[cfg(feature = "animation")]
+    check_animations(doc, ctx, &mut report).await;
+
+    report
+}
+
+async fn check_buffers(
+    doc: Document<'_>,
+    ctx: &mut LoadContext<'_>,
+    report: &mut ValidationReport,
+) {
+    for buffer in doc.buffers() {
+        let path = PathSegment::Buffer(buffer.index());
+
+        match buffer.load(ctx).await {
+            Ok(data) if data.len() < buffer.length() => report.error(
+                path,
+                DiagnosticCode::BufferLength,
+                format!(
+                    "buffer data is {} bytes but byteLength is {}",
+                    data.len(),
+                    buffer.length()
+                ),
+            ),
+            Ok(_) => {}
+            Err(e) => report.error(
+                path,
+                DiagnosticCode::DataUnavailable,
+                format!("buffer data could not be loaded: {e}"),
+            ),
+        }
+    }
+}
+
+fn check_views(doc: Document<'_>, report: &mut ValidationReport) {
+    for view in doc.views() {
+        let path = PathSegment::View(view.index());
+
+        match view.offset().checked_add(view.length()) {
+            Some(end) if end <= view.buffer().length() => {}
+            Some(end) => report.error(
+                path.clone(),
+                DiagnosticCode::ViewOutOfBounds,
+                format!(
+                    "buffer view ends at byte {end} but the buffer is {} bytes",
+                    view.buffer().length()
+                ),
+            ),
+            None => report.error(
+                path.clone(),
+                DiagnosticCode::ViewOutOfBounds,
+                String::from("buffer view end overflows the addressable range"),
+            ),
+        }
+
+        if let Some(stride) = view.stride() {
+            if !(4..=252).contains(&stride) || !stride.is_multiple_of(4) {
+                report.error(
+                    path,
+                    DiagnosticCode::InvalidStride,
+                    format!("byteStride {stride} must be a multiple of 4 between 4 and 252"),
+                );
+            }
+        }
+    }
+}
+
+fn check_node_hierarchy(doc: Document<'_>, report: &mut ValidationReport) {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Active,
+        Done,
+    }
+
+    let nodes = &doc.inner.doc;
+    let count = nodes.nodes().len();
+    let children: Vec<Vec<usize>> = nodes
+        .nodes()
+        .map(|n| n.children().map(|c| c.index()).collect())
+        .collect();
+
+    let mut parents = vec![0usize; count];
+    for child in children.iter().flatten() {
+        parents[*child] += 1;
+    }
+    for (index, &parents) in parents.iter().enumerate() {
+        if parents > 1 {
+            report.error(
+                node_path(doc, index),
+                DiagnosticCode::NodeMultipleParents,
+                format!("node is the child of {parents} nodes"),
+            );
+        }
+    }
+
+    // Iterative depth-first search which reports any back-edge as a cycle
+    let mut state = vec![Visit::New; count];
+    for root in 0..count {
+        if state[root] != Visit::New {
+            continue;
+        }
+
+        let mut stack = vec![(root, 0usize)];
+        state[root] = Visit::Active;
+
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            let Some(&child) = children[node].get(*next) else {
+                state[node] = Visit::Done;
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+
+            match state[child] {
+                Visit::New => {
+                    state[child] = Visit::Active;
+                    stack.push((child, 0));
+                }
+                Visit::Active => report.error(
+                    node_path(doc, node),
+                    DiagnosticCode::NodeCycle,
+                    format!("child node {child} is also an ancestor of this node"),
+                ),
+                Visit::Done => {}
+            }
+        }
+    }
+}
+
+fn node_path(doc: Document<'_>, index: usize) -> ObjectPath {
+    ObjectPath::from(PathSegment::Node {
+        index,
+        name: doc.get_node(index).and_then(|n| n.name()).map(String::from),
+    })
+}
+
+async fn check_accessor(
+    accessor: &Accessor<'_>,
+    ctx: &mut LoadContext<'_>,
+    report: &mut ValidationReport,
+) {
+    let path = ObjectPath::from(accessor.path_segment());
+    let component_size = accessor.data_type().size();
+
+    if let Some(view) = accessor.view() {
+        // Out of bounds views are reported by `check_views`
+        if !view_in_bounds(&view) {
+            return;
+        }
+
+        let stride = view.stride().unwrap_or(accessor.element_size());
+        if stride < accessor.element_size() {
+            report.error(
+                path.clone(),
+                DiagnosticCode::InvalidStride,
+                format!(
+                    "byteStride {stride} is smaller than the element size {}",
+                    accessor.element_size()
+                ),
+            );
+        }
+
+        // Only the remainders matter for alignment so this cannot overflow
+        let alignment = view.offset() % component_size + accessor.offset() % component_size;
+        if !alignment.is_multiple_of(component_size) {
+            report.error(
+                path.clone(),
+                DiagnosticCode::AccessorOutOfBounds,
+                format!("accessor data is not aligned to {component_size} bytes"),
+            );
+        }
+
+        let end = match accessor.len() {
+            0 => Some(accessor.offset()),
+            n => stride
+                .checked_mul(n - 1)
+                .and_then(|size| size.checked_add(accessor.element_size()))
+                .and_then(|size| size.checked_add(accessor.offset())),
+        };
+        match end {
+            Some(end) if end <= view.length() => {}
+            Some(end) => {
+                report.error(
+                    path.clone(),
+                    DiagnosticCode::AccessorOutOfBounds,
+                    format!(
+                        "accessor data ends at byte {end} but the buffer view is {} bytes",
+                        view.length()
+                    ),
+                );
+                return;
+            }
+            None => {
+                report.error(
+                    path.clone(),
+                    DiagnosticCode::AccessorOutOfBounds,
+                    String::from("accessor data end overflows the addressable range"),
+                );
+                return;
+            }
+        }
+    }
+
+    if let Some(sparse) = accessor.sparse() {
+        let indices = sparse.indices();
+        let index_size = match indices.index_type() {
+            gltf::accessor::sparse::IndexType::U8 => 1,
+            gltf::accessor::sparse::IndexType::U16 => 2,
+            gltf::accessor::sparse::IndexType::U32 => 4,
+        };
+        let values = sparse.values();
+
+        let fits = |offset: usize, size: usize, length: usize| {
+            sparse
+                .count()
+                .checked_mul(size)
+                .and_then(|size| size.checked_add(offset))
+                .is_some_and(|end| end <= length)
+        };
+        if !view_in_bounds(&indices.view()) || !view_in_bounds(&values.view()) {
+            return;
+        }
+        if !fits(indices.offset(), index_size, indices.view().length())
+            || !fits(
+                values.offset(),
+                accessor.element_size(),
+                values.view().length(),
+            )
+        {
+            report.error(
+                path.clone(),
+                DiagnosticCode::AccessorOutOfBounds,
+                String::from("sparse data exceeds the bounds of its buffer views"),
+            );
+            return;
+        }
+
+        if let Ok(indices) = indices.load(ctx).await {
+            let mut previous = None;
+            for n in 0..indices.count() {
+                let Some(index) = indices.get(n) else { break };
+                if index >= accessor.len() || previous.is_some_and(|p| p >= index) {
+                    report.error(
+                        path.clone(),
+                        DiagnosticCode::InvalidSparseIndices,
+                        format!(
+                            "sparse index {index} at position {n} is out of range or not strictly increasing"
+                        ),
+                    );
+                    break;
+                }
+                previous = Some(index);
+            }
+        }
+    }
+
+    // Non-finite values are reported below. Loading skips the check done by
+    // `Accessor::load_untyped` so they are not also reported to the Document
+    let data = match accessor.load_data(ctx).await {
+        Ok(data) => data,
+        Err(e) => {
+            report.error(
+                path,
+                DiagnosticCode::DataUnavailable,
+                format!("accessor data could not be loaded: {}", e.root_cause()),
+            );
+            return;
+        }
+    };
+
+    check_accessor_values(accessor, &data, path, report);
+}
+
+/// Returns true if the [View] lies within the declared length of its buffer
+fn view_in_bounds(view: &View<'_>) -> bool {
+    view.offset()
+        .checked_add(view.length())
+        .is_some_and(|end| end <= view.buffer().length())
+}
+
+/// Check the `min` and `max` properties and for non-finite floating point values
+fn check_accessor_values(
+    accessor: &Accessor<'_>,
+    data: &Data<'_, Untyped>,
+    path: ObjectPath,
+    report: &mut ValidationReport,
+) {
+    let data_type = accessor.data_type();
+    let components = accessor.dimensions().multiplicity();
+
+    // Matrices of small component types contain column padding
+    let is_matrix = matches!(
+        accessor.dimensions(),
+        Dimensions::Mat2 | Dimensions::Mat3 | Dimensions::Mat4
+    );
+    if is_matrix && data_type.size() < 4 {
+        return;
+    }
+
+    let mut min = vec![f64::INFINITY; components];
+    let mut max = vec![f64::NEG_INFINITY; components];
+    let mut non_finite = false;
+
+    for i in 0..accessor.len() {
+        let Some(raw) = data.get_raw(i) else { break };
+
+        for (c, value) in read_components(raw, data_type).enumerate().take(components) {
+            non_finite |= !value.is_finite();
+            min[c] = min[c].min(value);
+            max[c] = max[c].max(value);
+        }
+    }
+
+    if non_finite {
+        report.error(
+            path.clone(),
+            DiagnosticCode::NonFiniteValue,
+            String::from("accessor contains Infinite or NaN f32 value"),
+        );
+        return;
+    }
+
+    if accessor.is_empty() {
+        return;
+    }
+
+    for (name, declared, actual) in [("min", accessor.min(), &min), ("max", accessor.max(), &max)] {
+        let Some(declared) = declared else { continue };
+        let declared: Option<Vec<f64>> = declared
+            .as_array()
+            .map(|values| values.iter().filter_map(|v| v.as_f64()).collect());
+
+        match declared {
+            Some(declared) if declared.len() == components => {
+                let mismatch = declared
+                    .iter()
+                    .zip(actual)
+                    .position(|(d, a)| !bounds_equal(*d, *a, data_type));
+
+                if let Some(c) = mismatch {
+                    report.error(
+                        path.clone(),
+                        DiagnosticCode::AccessorBounds,
+                        format!(
+                            "declared {name} {} of component {c} does not match actual {name} {}",
+                            declared[c], actual[c]
+                        ),
+                    );
+                }
+            }
+            _ => report.error(
+                path.clone(),
+                DiagnosticCode::AccessorBounds,
+                format!("declared {name} must contain {components} numbers"),
+            ),
+        }
+    }
+}
+
+fn bounds_equal(declared: f64, actual: f64, data_type: DataType) -> bool {
+    match data_type {
+        DataType::F32 => (declared as f32) == (actual as f32),
+        _ => declared == actual,
+    }
+}
+
+fn read_components(raw: &[u8], data_type: DataType) -> impl Iterator<Item = f64> + '_ {
+    raw.chunks_exact(data_type.size())
+        .map(move |c| match data_type {
+            DataType::I8 => c[0] as i8 as f64,
+            DataType::U8 => c[0] as f64,
+            DataType::I16 => i16::from_le_bytes([c[0], c[1]]) as f64,
+            DataType::U16 => u16::from_le_bytes([c[0], c[1]]) as f64,
+            DataType::U32 => u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f64,
+            DataType::F32 => f32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f64,
+        })
+}
+
+async fn check_meshes(doc: Document<'_>, ctx: &mut LoadContext<'_>, report: &mut ValidationReport) {
+    for mesh in doc.meshes() {
+        // Skins used alongside this mesh determine the valid joint indices
+        let joint_counts: Vec<usize> = doc
+            .inner
+            .doc
+            .nodes()
+            .filter(|n| n.mesh().is_some_and(|m| m.index() == mesh.index()))
+            .filter_map(|n| n.skin())
+            .map(|s| s.joints().len())
+            .collect();
+
+        for primitive in mesh.primitives() {
+            let path = ObjectPath::from(mesh.path_segment()).join(primitive.path_segment());
+            let attributes: Vec<(Semantic, Accessor)> = primitive.attributes().collect();
+
+            // All attributes must have the same number of elements
+            let vertex_count = primitive
+                .get_accessor(&Semantic::Positions)
+                .map(|a| a.len())
+                .or_else(|| attributes.first().map(|(_, a)| a.len()));
+
+            for (semantic, accessor) in &attributes {
+                let attr_path = path
+                    .clone()
+                    .join(PathSegment::Attribute(semantic.to_string()))
+                    .join(accessor.path_segment());
+
+                if vertex_count.is_some_and(|count| count != accessor.len()) {
+                    report.error(
+                        attr_path.clone(),
+                        DiagnosticCode::AttributeCountMismatch,
+                        format!(
+                            "attribute has {} elements but the primitive has {} vertices",
+                            accessor.len(),
+                            vertex_count.unwrap_or_default()
+                        ),
+                    );
+                }
+
+                match semantic {
+                    Semantic::Normals => {
+                        check_unit_vectors(accessor, ctx, attr_path, false, report).await
+                    }
+                    Semantic::Tangents => {
+                        check_unit_vectors(accessor, ctx, attr_path, true, report).await
+                    }
+                    Semantic::Joints(set) => {
+                        if primitive.get_accessor(&Semantic::Weights(*set)).is_none() {
+                            report.error(
+                                attr_path.clone(),
+                                DiagnosticCode::JointWeightMismatch,
+                                format!("JOINTS_{set} has no matching WEIGHTS_{set} attribute"),
+                            );
+                        }
+
+                        if let Some(max_joints) = joint_counts.iter().min() {
+                            check_joint_indices(accessor, ctx, attr_path, *max_joints, report)
+                                .await;
+                        }
+                    }
+                    Semantic::Weights(set) => {
+                        if primitive.get_accessor(&Semantic::Joints(*set)).is_none() {
+                            report.error(
+                                attr_path.clone(),
+                                DiagnosticCode::JointWeightMismatch,
+                                format!("WEIGHTS_{set} has no matching JOINTS_{set} attribute"),
+                            );
+                        }
+
+                        // Weights can only be checked for normalization when
+                        // there is a single set of weights
+                        if *set == 0 && primitive.get_accessor(&Semantic::Weights(1)).is_none() {
+                            check_weight_sums(accessor, ctx, attr_path, report).await;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if let (Some(indices), Some(vertex_count)) = (primitive.indices(), vertex_count) {
+                let index_path = path
+                    .clone()
+                    .join(PathSegment::Attribute(String::from("indices")))
+                    .join(indices.path_segment());
+                check_indices(&indices, ctx, index_path, vertex_count, report).await;
+            }
+        }
+    }
+}
+
+async fn check_unit_vectors(
+    accessor: &Accessor<'_>,
+    ctx: &mut LoadContext<'_>,
+    path: ObjectPath,
+    tangent: bool,
+    report: &mut ValidationReport,
+) {
+    let lengths: Vec<(f32, Option<f32>)> = if tangent {
+        let Ok(data) = accessor.load_unchecked::<[f32; 4]>(ctx).await else {
+            return;
+        };
+        data.iter()
+            .map(|[x, y, z, w]| ((x * x + y * y + z * z).sqrt(), Some(w)))
+            .collect()
+    } else {
+        let Ok(data) = accessor.load_unchecked::<[f32; 3]>(ctx).await else {
+            return;
+        };
+        data.iter()
+            .map(|[x, y, z]| ((x * x + y * y + z * z).sqrt(), None))
+            .collect()
+    };
+
+    for (i, (length, w)) in lengths.into_iter().enumerate() {
+        if (length - 1.0).abs() > UNIT_LENGTH_TOLERANCE {
+            report.error(
+                path.clone(),
+                DiagnosticCode::NonUnitVector,
+                format!("element {i} has length {length}, expected 1.0"),
+            );
+            return;
+        }
+
+        if w.is_some_and(|w| w != 1.0 && w != -1.0) {
+            report.error(
+                path.clone(),
+                DiagnosticCode::NonUnitVector,
+                format!("element {i} has a tangent w component other than 1.0 or -1.0"),
+            );
+            return;
+        }
+    }
+}
+
+async fn check_joint_indices(
+    accessor: &Accessor<'_>,
+    ctx: &mut LoadContext<'_>,
+    path: ObjectPath,
+    joint_count: usize,
+    report: &mut ValidationReport,
+) {
+    let Ok(data) = accessor.load_unchecked::<AttrJointIndex>(ctx).await else {
+        return;
+    };
+
+    if let Some((i, joint)) = data.iter().enumerate().find_map(|(i, joints)| {
+        joints
+            .into_iter()
+            .find(|j| *j as usize >= joint_count)
+            .map(|j| (i, j))
+    }) {
+        report.error(
+            path,
+            DiagnosticCode::IndexOutOfRange,
+            format!("element {i} references joint {joint} but the skin has {joint_count} joints"),
+        );
+    }
+}
+
+async fn check_weight_sums(
+    accessor: &Accessor<'_>,
+    ctx: &mut LoadContext<'_>,
+    path: ObjectPath,
+    report: &mut ValidationReport,
+) {
+    let Ok(data) = accessor.load_unchecked::<AttrJointWeight>(ctx).await else {
+        return;
+    };
+
+    if let Some((i, sum)) = data
+        .iter()
+        .map(|w| w.iter().sum::<f32>())
+        .enumerate()
+        .find(|(_, sum)| (sum - 1.0).abs() > WEIGHT_SUM_TOLERANCE)
+    {
+        report.error(
+            path,
+            DiagnosticCode::JointWeightMismatch,
+            format!("weights of element {i} sum to {sum}, expected 1.0"),
+        );
+    }
+}
+
+async fn check_indices(
+    indices: &Accessor<'_>,
+    ctx: &mut LoadContext<'_>,
+    path: ObjectPath,
+    vertex_count: usize,
+    report: &mut ValidationReport,
+) {
+    let values: Vec<usize> = match indices.data_type() {
+        DataType::U8 => match indices.load_unchecked::<u8>(ctx).await {
+            Ok(d) => d.iter().map(|i| i as usize).collect(),
+            Err(_) => return,
+        },
+        DataType::U16 => match indices.load_unchecked::<u16>(ctx).await {
+            Ok(d) => d.iter().map(|i| i as usize).collect(),
+            Err(_) => return,
+        },
+        DataType::U32 => match indices.load_unchecked::<u32>(ctx).await {
+            Ok(d) => d.iter().map(|i| i as usize).collect(),
+            Err(_) => return,
+        },
+        other => {
+            report.error(
+                path,
+                DiagnosticCode::IndexOutOfRange,
+                format!("indices must be an unsigned integer type, found {other:?}"),
+            );
+            return;
+        }
+    };
+
+    if let Some((i, index)) = values
+        .into_iter()
+        .enumerate()
+        .find(|(_, v)| *v >= vertex_count)
+    {
+        report.error(
+            path,
+            DiagnosticCode::IndexOutOfRange,
+            format!("index {index} at position {i} exceeds the vertex count {vertex_count}"),
+        );
+    }
+}
+
+#[cfg(feature = "animation")]
+async fn check_animations(
+    doc: Document<'_>,
+    ctx: &mut LoadContext<'_>,
+    report: &mut ValidationReport,
+) {
+    for animation in doc.animations() {
+        for channel in animation.channels() {
+            let input = channel.sampler().input();
+            let path = ObjectPath::from(animation.path_segment())
+                .join(PathSegment::Channel(channel.index()))
+                .join(input.path_segment());
+
+            let times = match input.load_unchecked::<f32>(ctx).await {
+                Ok(times) => times,
+                Err(e) if matches!(e.root_cause(), Error::AccessorType { .. }) => {
+                    report.error(
+                        path,
+                        DiagnosticCode::AnimationInput,
+                        String::from("animation input must be scalar f32 values"),
+                    );
+                    continue;
+                }
+                // Unavailable data is reported by `check_accessor`
+                Err(_) => continue,
+            };
+
+            let mut previous: Option<f32> = None;
+            for (i, time) in times.iter().enumerate() {
+                if time < 0.0 || previous.is_some_and(|p| p >= time) {
+                    report.error(
+                        path.clone(),
+                        DiagnosticCode::AnimationInput,
+                        format!(
+                            "keyframe {i} at time {time} is negative or not strictly increasing"
+                        ),
+                    );
+                    break;
+                }
+                previous = Some(time);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{gltf_file, run_document_test, DocumentTest};
+    use serde_json::{json, Value};
+
+    struct Validate;
+
+    impl DocumentTest for Validate {
+        type Output = (ValidationReport, Vec<Diagnostic>);
+
+        async fn run(&self, doc: Document<'_>, ctx: &mut LoadContext<'_>) -> Self::Output {
+            let report = doc.validate(ctx).await;
+            (report, doc.diagnostics().to_vec())
+        }
+    }
+
+    fn validate(json: Value, bin: &[u8]) -> ValidationReport {
+        run_document_test(
+            "test.gltf",
+            &[("test.gltf", gltf_file(json, bin))],
+            Validate,
+        )
+        .0
+    }
+
+    fn codes(report: &ValidationReport) -> Vec<DiagnosticCode> {
+        report.issues.iter().map(|d| d.code).collect()
+    }
+
+    fn bytes<T: Copy, const N: usize>(values: &[T], to_bytes: fn(T) -> [u8; N]) -> Vec<u8> {
+        values.iter().flat_map(|v| to_bytes(*v)).collect()
+    }
+
+    fn f32s(values: &[f32]) -> Vec<u8> {
+        bytes(values, f32::to_le_bytes)
+    }
+
+    const POSITIONS: usize = 0;
+    const NORMALS: usize = 36;
+    const TANGENTS: usize = 72;
+    const JOINTS: usize = 120;
+    const WEIGHTS: usize = 132;
+    const INDICES: usize = 180;
+    const TIMES: usize = 188;
+
+    /// A valid skinned and animated triangle
+    fn triangle() -> (Value, Vec<u8>) {
+        let mut bin = Vec::new();
+        bin.extend(f32s(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]));
+        bin.extend(f32s(&[0.0, 0.0, 1.0].repeat(3)));
+        bin.extend(f32s(&[1.0, 0.0, 0.0, 1.0].repeat(3)));
+        bin.extend([0u8; 12]);
+        bin.extend(f32s(&[1.0, 0.0, 0.0, 0.0].repeat(3)));
+        bin.extend(bytes(&[0u16, 1, 2, 0], u16::to_le_bytes));
+        bin.extend(f32s(&[0.0, 1.0]));
+        bin.extend(f32s(&[0.0; 6]));
+
+        let json = json!({
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": POSITIONS, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": NORMALS, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": TANGENTS, "byteLength": 48 },
+                { "buffer": 0, "byteOffset": JOINTS, "byteLength": 12 },
+                { "buffer": 0, "byteOffset": WEIGHTS, "byteLength": 48 },
+                { "buffer": 0, "byteOffset": INDICES, "byteLength": 6 },
+                { "buffer": 0, "byteOffset": TIMES, "byteLength": 8 },
+                { "buffer": 0, "byteOffset": TIMES + 8, "byteLength": 24 },
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                  "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] },
+                { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" },
+                { "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC4" },
+                { "bufferView": 3, "componentType": 5121, "count": 3, "type": "VEC4" },
+                { "bufferView": 4, "componentType": 5126, "count": 3, "type": "VEC4" },
+                { "bufferView": 5, "componentType": 5123, "count": 3, "type": "SCALAR" },
+                { "bufferView": 6, "componentType": 5126, "count": 2, "type": "SCALAR",
+                  "min": [0.0], "max": [1.0] },
+                { "bufferView": 7, "componentType": 5126, "count": 2, "type": "VEC3" },
+            ],
+            "meshes": [{
+                "primitives": [{
+                    "attributes": {
+                        "POSITION": 0, "NORMAL": 1, "TANGENT": 2, "JOINTS_0": 3, "WEIGHTS_0": 4,
+                    },
+                    "indices": 5,
+                }],
+            }],
+            "skins": [{ "joints": [1] }],
+            "nodes": [{ "mesh": 0, "skin": 0 }, {}],
+            "animations": [{
+                "channels": [{ "sampler": 0, "target": { "node": 1, "path": "translation" } }],
+                "samplers": [{ "input": 6, "output": 7 }],
+            }],
+            "scenes": [{ "nodes": [0, 1] }],
+        });
+
+        (json, bin)
+    }
+
+    fn write_f32(bin: &mut [u8], offset: usize, value: f32) {
+        bin[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn valid() {
+        let (json, bin) = triangle();
+        let report = validate(json, &bin);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn buffer_length() {
+        let (mut json, bin) = triangle();
+        let report = validate(json.clone(), &bin);
+        assert!(report.is_valid());
+
+        json["buffers"] = gltf_file_buffers(&bin[..100], bin.len());
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::BufferLength));
+    }
+
+    #[test]
+    fn buffer_length_accessor_offset() {
+        // The view starts inside the truncated data, the accessor past its end
+        let (mut json, bin) = triangle();
+        json["bufferViews"][2]["byteLength"] = json!(80);
+        json["accessors"][2]["byteOffset"] = json!(32);
+        json["buffers"] = gltf_file_buffers(&bin[..100], bin.len());
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::BufferLength));
+    }
+
+    /// A `buffers` array holding `data` while declaring `length` bytes
+    fn gltf_file_buffers(data: &[u8], length: usize) -> Value {
+        let file: Value = serde_json::from_slice(&gltf_file(json!({}), data)).unwrap();
+        let mut buffers = file["buffers"].clone();
+        buffers[0]["byteLength"] = json!(length);
+        buffers
+    }
+
+    #[test]
+    fn buffer_unavailable() {
+        let report = validate(
+            json!({ "buffers": [{ "byteLength": 4, "uri": "missing.bin" }] }),
+            &[],
+        );
+        assert_eq!(codes(&report), [DiagnosticCode::DataUnavailable]);
+    }
+
+    #[test]
+    fn view_out_of_bounds() {
+        let (mut json, bin) = triangle();
+        json["bufferViews"][7]["byteLength"] = json!(32);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::ViewOutOfBounds));
+    }
+
+    #[test]
+    fn view_offset_overflow() {
+        let (mut json, bin) = triangle();
+        json["bufferViews"][7]["byteOffset"] = json!(u64::MAX - 4);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::ViewOutOfBounds));
+    }
+
+    #[test]
+    fn view_stride() {
+        let (mut json, bin) = triangle();
+        json["bufferViews"][0]["byteStride"] = json!(6);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::InvalidStride));
+    }
+
+    #[test]
+    fn node_multiple_parents() {
+        let (mut json, bin) = triangle();
+        json["nodes"] =
+            json!([{ "mesh": 0, "skin": 0 }, {}, { "children": [1] }, { "children": [1] }]);
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::NodeMultipleParents]);
+    }
+
+    #[test]
+    fn node_cycle() {
+        let (mut json, bin) = triangle();
+        json["nodes"] = json!([{ "mesh": 0, "skin": 0 }, { "children": [2] }, { "children": [1] }]);
+        json["scenes"] = json!([{ "nodes": [0] }]);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::NodeCycle));
+    }
+
+    #[test]
+    fn accessor_stride() {
+        let (mut json, bin) = triangle();
+        json["bufferViews"][0]["byteStride"] = json!(8);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::InvalidStride));
+    }
+
+    #[test]
+    fn accessor_alignment() {
+        let (mut json, bin) = triangle();
+        json["bufferViews"][6]["byteLength"] = json!(12);
+        json["accessors"][6]["byteOffset"] = json!(2);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::AccessorOutOfBounds));
+    }
+
+    #[test]
+    fn accessor_out_of_bounds() {
+        let (mut json, bin) = triangle();
+        json["accessors"][7]["count"] = json!(3);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::AccessorOutOfBounds));
+    }
+
+    #[test]
+    fn accessor_count_overflow() {
+        let (mut json, bin) = triangle();
+        json["accessors"][7]["count"] = json!(u64::MAX / 4);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::AccessorOutOfBounds));
+    }
+
+    /// Adds a sparse accessor replacing the times with the values at `TIMES`
+    fn sparse_times(json: &mut Value, count: u64, indices: usize) {
+        json["accessors"][6]["sparse"] = json!({
+            "count": count,
+            "indices": { "bufferView": indices, "componentType": 5123 },
+            "values": { "bufferView": 6 },
+        });
+    }
+
+    #[test]
+    fn sparse_out_of_bounds() {
+        let (mut json, bin) = triangle();
+        sparse_times(&mut json, 4, 5);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::AccessorOutOfBounds));
+    }
+
+    #[test]
+    fn sparse_count_overflow() {
+        let (mut json, bin) = triangle();
+        sparse_times(&mut json, u64::MAX / 2, 5);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::AccessorOutOfBounds));
+    }
+
+    #[test]
+    fn sparse_indices() {
+        // Replace both times using the indices 0 and 1
+        let (mut json, mut bin) = triangle();
+        sparse_times(&mut json, 2, 5);
+        let report = validate(json.clone(), &bin);
+        assert!(report.is_valid(), "{:?}", report.issues);
+
+        // Indices 1 and 2, where 2 is out of range
+        json["accessors"][6]["sparse"]["indices"]["byteOffset"] = json!(2);
+        let report = validate(json.clone(), &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::InvalidSparseIndices));
+
+        // Indices 1 and 0, which are not increasing
+        json["accessors"][6]["sparse"]["indices"]["byteOffset"] = json!(0);
+        bin[INDICES..INDICES + 4].copy_from_slice(&bytes(&[1u16, 0], u16::to_le_bytes));
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::InvalidSparseIndices));
+    }
+
+    #[test]
+    fn accessor_unavailable() {
+        let (mut json, bin) = triangle();
+        json["buffers"] = json!([{ "byteLength": bin.len(), "uri": "missing.bin" }]);
+        let report = validate(json, &bin);
+        let codes = codes(&report);
+        assert!(codes.contains(&DiagnosticCode::DataUnavailable));
+        assert!(
+            codes.iter().all(|c| *c == DiagnosticCode::DataUnavailable),
+            "{codes:?}"
+        );
+    }
+
+    #[test]
+    fn non_finite() {
+        let (json, mut bin) = triangle();
+        write_f32(&mut bin, NORMALS, f32::NAN);
+
+        let (report, diagnostics) = run_document_test(
+            "test.gltf",
+            &[("test.gltf", gltf_file(json, &bin))],
+            Validate,
+        );
+        assert!(codes(&report).contains(&DiagnosticCode::NonFiniteValue));
+
+        // The validator must not also report the value through the document
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code != DiagnosticCode::NonFiniteValue));
+    }
+
+    #[test]
+    fn accessor_bounds() {
+        let (mut json, bin) = triangle();
+        json["accessors"][0]["max"] = json!([1.0, 2.0, 0.0]);
+        let report = validate(json.clone(), &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::AccessorBounds]);
+
+        let (mut json, bin) = triangle();
+        json["accessors"][6]["max"] = json!([1.0, 2.0]);
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::AccessorBounds]);
+    }
+
+    #[test]
+    fn attribute_count() {
+        let (mut json, bin) = triangle();
+        json["accessors"][1]["count"] = json!(2);
+        let report = validate(json, &bin);
+        assert!(codes(&report).contains(&DiagnosticCode::AttributeCountMismatch));
+    }
+
+    #[test]
+    fn non_unit_normal() {
+        let (json, mut bin) = triangle();
+        write_f32(&mut bin, NORMALS + 8, 2.0);
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::NonUnitVector]);
+    }
+
+    #[test]
+    fn tangent_handedness() {
+        let (json, mut bin) = triangle();
+        write_f32(&mut bin, TANGENTS + 12, 0.5);
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::NonUnitVector]);
+    }
+
+    #[test]
+    fn joints_without_weights() {
+        let (mut json, bin) = triangle();
+        json["meshes"][0]["primitives"][0]["attributes"]
+            .as_object_mut()
+            .unwrap()
+            .remove("WEIGHTS_0");
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::JointWeightMismatch]);
+    }
+
+    #[test]
+    fn weight_sums() {
+        let (json, mut bin) = triangle();
+        write_f32(&mut bin, WEIGHTS, 0.5);
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::JointWeightMismatch]);
+    }
+
+    #[test]
+    fn joint_out_of_range() {
+        let (json, mut bin) = triangle();
+        bin[JOINTS] = 1;
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::IndexOutOfRange]);
+    }
+
+    #[test]
+    fn index_out_of_range() {
+        let (json, mut bin) = triangle();
+        bin[INDICES..INDICES + 2].copy_from_slice(&3u16.to_le_bytes());
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::IndexOutOfRange]);
+    }
+
+    #[cfg(feature = "animation")]
+    #[test]
+    fn animation_input() {
+        let (mut json, mut bin) = triangle();
+        write_f32(&mut bin, TIMES, 2.0);
+        json["accessors"][6]["min"] = json!([1.0]);
+        json["accessors"][6]["max"] = json!([2.0]);
+        let report = validate(json, &bin);
+        assert_eq!(codes(&report), [DiagnosticCode::AnimationInput]);
+    }
+}